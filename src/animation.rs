@@ -0,0 +1,270 @@
+#![deny(missing_docs)]
+
+//! Renders a sequence of images whose views are scripted by keyframes.
+//!
+//! A keyframe file lists one keyframe per line with six whitespace-separated columns:
+//!
+//! ```text
+//! # time  center_x  center_y  zoom  rotation  palette_offset
+//!   0.0   -0.75     0.0       1.    0.        0.
+//!   10.0  -0.7435   0.1314    1.e3  3.14      1.
+//! ```
+//!
+//! Everything after `#` is ignored.
+//! The image width spans [`mandelbrot::BASE_SPAN`] / `zoom` in the complex plane,
+//! the rotation angle is given in radians, and the palette offset is given in cycles.
+
+use crate::coord::Coord;
+use crate::error::{Error, Malformed};
+use crate::mandelbrot::{self, Point};
+use crate::options::Options;
+use crate::output;
+use crate::text;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// Stores a view of the complex plane at a specific time.
+pub struct Keyframe {
+    /// time at which this view is displayed
+    pub time: f64,
+    /// center of the image
+    pub center: Coord<f64>,
+    /// magnification of the image
+    pub zoom: f64,
+    /// rotation angle of the image in radians
+    pub rotation: f64,
+    /// phase shift of the colour palette in cycles
+    pub palette_offset: f64,
+}
+
+/// Main function, which renders all frames described by the given keyframe file.
+///
+/// Frames are distributed to `options.threads` workers,
+/// and those before `options.resume_from` are skipped.
+///
+/// * `options` - Fixed configuration parameters.
+/// * `fname`   - Name of the keyframe file.
+pub fn execute(options: &Options, fname: &str) -> Result<(), Error> {
+    let keyframes: Vec<Keyframe> = load(fname)?;
+    let nframes: usize = {
+        let duration: f64 = keyframes[keyframes.len() - 1].time - keyframes[0].time;
+        (duration * options.fps).floor() as usize + 1
+    };
+    if nframes <= options.resume_from {
+        eprintln!(
+            "nothing to render: {} frames in total, resumed from {}",
+            nframes, options.resume_from
+        );
+        return Ok(());
+    }
+    eprintln!(
+        "rendering frames {} to {} using {} threads",
+        options.resume_from,
+        nframes - 1,
+        options.threads
+    );
+    // each worker takes the next frame index until all frames are done
+    let next: AtomicUsize = AtomicUsize::new(options.resume_from);
+    let failed: AtomicBool = AtomicBool::new(false);
+    // the first failure is reported
+    let failure: Mutex<Option<Error>> = Mutex::new(None);
+    std::thread::scope(|scope| {
+        for _ in 0..options.threads {
+            scope.spawn(|| loop {
+                let index: usize = next.fetch_add(1, Ordering::Relaxed);
+                if nframes <= index || failed.load(Ordering::Relaxed) {
+                    break;
+                }
+                if let Err(error) = render_frame(options, &keyframes, index) {
+                    eprintln!("failed to render frame {}", index);
+                    failed.store(true, Ordering::Relaxed);
+                    if let Ok(mut failure) = failure.lock() {
+                        failure.get_or_insert(error);
+                    }
+                }
+            });
+        }
+    });
+    if let Some(error) = failure.into_inner().ok().flatten() {
+        eprintln!("re-run with --resume_from to continue");
+        return Err(error);
+    }
+    return Ok(());
+}
+
+/// Renders a single frame and outputs it as an image.
+///
+/// * `options`   - Fixed configuration parameters, which are shared among all frames.
+/// * `keyframes` - Sequence of keyframes.
+/// * `index`     - Index of the frame to be rendered.
+fn render_frame(options: &Options, keyframes: &[Keyframe], index: usize) -> Result<(), Error> {
+    let time: f64 = keyframes[0].time + index as f64 / options.fps;
+    let keyframe: Keyframe = interpolate(keyframes, time);
+    let mut frame: Options = options.clone();
+    frame.grid_size = mandelbrot::BASE_SPAN / (keyframe.zoom * options.resols.x as f64);
+    frame.rotation = keyframe.rotation;
+    frame.palette_offset = keyframe.palette_offset;
+    frame.fname = output::suffixed_fname(&options.fname, &format!("{:05}", index))?;
+    let points: Vec<Point> = mandelbrot::solve_recurrence_relation(&frame, &keyframe.center)?;
+    match output::execute(&frame, &keyframe.center, points) {
+        Ok(_) => {}
+        Err(error) => return Err(error),
+    };
+    eprintln!("frame {} is written to {}", index, frame.fname);
+    return Ok(());
+}
+
+/// Loads and validates a keyframe file.
+///
+/// * `fname` - Name of the keyframe file.
+fn load(fname: &str) -> Result<Vec<Keyframe>, Error> {
+    let contents: String = match std::fs::read_to_string(fname) {
+        Ok(contents) => contents,
+        Err(error) => return Err(Error::Read(String::from(fname), error)),
+    };
+    return match parse(&contents) {
+        Ok(keyframes) => Ok(keyframes),
+        Err(msg) => Err(Error::Parse(String::from(fname), msg)),
+    };
+}
+
+/// Converts the contents of a keyframe file to a sequence of keyframes.
+///
+/// * `contents` - Whole text of the keyframe file.
+fn parse(contents: &str) -> Result<Vec<Keyframe>, Malformed> {
+    let mut keyframes: Vec<Keyframe> = Vec::new();
+    for (n, line) in text::lines(contents) {
+        let mut values: Vec<f64> = Vec::with_capacity(6);
+        for word in line.split_whitespace() {
+            match word.parse::<f64>() {
+                Ok(value) => values.push(value),
+                Err(error) => {
+                    return Err(Malformed::caused(
+                        format!("line {}: invalid value {}", n + 1, word),
+                        error,
+                    ))
+                }
+            };
+        }
+        if 6 != values.len() {
+            return Err(format!("line {}: expect 6 columns", n + 1).into());
+        }
+        if values.iter().any(|value| !value.is_finite()) {
+            return Err(format!("line {}: expect finite values", n + 1).into());
+        }
+        if values[3] <= 0. {
+            return Err(format!("line {}: expect positive zoom", n + 1).into());
+        }
+        if let Some(last) = keyframes.last() {
+            if values[0] <= last.time {
+                return Err(format!("line {}: expect increasing time", n + 1).into());
+            }
+        }
+        keyframes.push(Keyframe {
+            time: values[0],
+            center: Coord {
+                x: values[1],
+                y: values[2],
+            },
+            zoom: values[3],
+            rotation: values[4],
+            palette_offset: values[5],
+        });
+    }
+    if keyframes.is_empty() {
+        return Err(String::from("no keyframe is found").into());
+    }
+    return Ok(keyframes);
+}
+
+/// Computes the view at the given time from the two surrounding keyframes.
+///
+/// The zoom is interpolated geometrically so that the zoom-in speed is kept constant,
+/// and the center is interpolated linearly in the inverse of the zoom,
+/// so that the view zooms around a point fixed in the frame and the target never leaves it.
+/// The others are interpolated linearly.
+///
+/// * `keyframes` - Sequence of keyframes, whose time should be increasing.
+/// * `time`      - Time of interest.
+fn interpolate(keyframes: &[Keyframe], time: f64) -> Keyframe {
+    // find the last keyframe displayed before the given time
+    let mut k: usize = 0;
+    while k + 2 < keyframes.len() && keyframes[k + 1].time <= time {
+        k += 1;
+    }
+    let k0: &Keyframe = &keyframes[k];
+    let k1: &Keyframe = &keyframes[if k + 1 < keyframes.len() { k + 1 } else { k }];
+    let s: f64 = if k1.time == k0.time {
+        0.
+    } else {
+        ((time - k0.time) / (k1.time - k0.time)).clamp(0., 1.)
+    };
+    let lerp = |v0: f64, v1: f64| -> f64 { (1. - s) * v0 + s * v1 };
+    let zoom: f64 = k0.zoom * (k1.zoom / k0.zoom).powf(s);
+    // fraction of the move of the center, which is proportional to the change of the view size
+    let t: f64 = if k1.zoom == k0.zoom {
+        s
+    } else {
+        (1. / k0.zoom - 1. / zoom) / (1. / k0.zoom - 1. / k1.zoom)
+    };
+    return Keyframe {
+        time,
+        center: Coord {
+            x: (1. - t) * k0.center.x + t * k1.center.x,
+            y: (1. - t) * k0.center.y + t * k1.center.y,
+        },
+        zoom,
+        rotation: lerp(k0.rotation, k1.rotation),
+        palette_offset: lerp(k0.palette_offset, k1.palette_offset),
+    };
+}
+
+#[cfg(test)]
+mod test_animation {
+    use crate::animation::{interpolate, parse, Keyframe};
+    #[test]
+    fn test_parse0() -> () {
+        let keyframes: Vec<Keyframe> =
+            parse("# comment\n0. -0.5 0. 1. 0. 0.\n\n2. 0.5 1. 100. 1. 1. # last\n").unwrap();
+        assert_eq!(2, keyframes.len());
+        assert_eq!(100., keyframes[1].zoom);
+    }
+    #[test]
+    fn test_parse1() -> () {
+        assert!(parse("0. 0. 0. 1. 0.\n").is_err());
+        assert!(parse("0. 0. 0. 0. 0. 0.\n").is_err());
+        assert!(parse("1. 0. 0. 1. 0. 0.\n0. 0. 0. 1. 0. 0.\n").is_err());
+        assert!(parse("# nothing\n").is_err());
+        assert!(parse("0. nan 0. 1. 0. 0.\n").is_err());
+        assert!(parse("0. 0. 0. inf 0. 0.\n").is_err());
+    }
+    #[test]
+    fn test_interpolate() -> () {
+        let keyframes: Vec<Keyframe> = parse("0. 0. 0. 1. 0. 0.\n2. 1. 2. 100. 1. 1.\n").unwrap();
+        let keyframe: Keyframe = interpolate(&keyframes, 1.);
+        // the view size 1 / zoom has shrunk by 0.9 out of 0.99
+        assert!((keyframe.center.x - 0.9 / 0.99).abs() < 1e-12);
+        assert!((keyframe.center.y - 1.8 / 0.99).abs() < 1e-12);
+        assert!((keyframe.zoom - 10.).abs() < 1e-12);
+        assert!((keyframe.rotation - 0.5).abs() < 1e-12);
+        let keyframe: Keyframe = interpolate(&keyframes, 3.);
+        assert!((keyframe.zoom - 100.).abs() < 1e-12);
+        // the point zoomed around keeps its position in the frame,
+        //   which is next to the target of a deep zoom
+        let keyframes: Vec<Keyframe> =
+            parse("0. -0.5 0. 1. 0. 0.\n1. -0.7435 0.1314 1.e6 0. 0.\n").unwrap();
+        let target: [f64; 2] = [-0.5 + (-0.7435 + 0.5) / (1. - 1e-6), 0.1314 / (1. - 1e-6)];
+        let frame = |keyframe: &Keyframe| -> [f64; 2] {
+            return [
+                (target[0] - keyframe.center.x) * keyframe.zoom,
+                (target[1] - keyframe.center.y) * keyframe.zoom,
+            ];
+        };
+        let expected: [f64; 2] = frame(&keyframes[0]);
+        for time in [0.1, 0.5, 0.9] {
+            let position: [f64; 2] = frame(&interpolate(&keyframes, time));
+            assert!((position[0] - expected[0]).abs() < 1e-6);
+            assert!((position[1] - expected[1]).abs() < 1e-6);
+        }
+    }
+}
@@ -0,0 +1,164 @@
+#![deny(missing_docs)]
+
+//! Writes files atomically, so that a crash or a full disk never leaves a truncated file in place of the old one.
+//!
+//! The contents are written to a temporary file in the same directory,
+//! flushed and synced to the disk, and then renamed over the target,
+//! which replaces the old file at once on the same file system.
+//! The permissions of the old file are carried over to the new one.
+
+use crate::error::Error;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Writes the contents to a file atomically.
+///
+/// The temporary file is removed when any step fails.
+/// Once renamed, the file is in place even if the directory fails to be synced,
+/// which is thus reported as a warning instead of a failure.
+///
+/// * `fname` - Name of the target file.
+/// * `bytes` - Contents of the file.
+pub fn write(fname: &str, bytes: &[u8]) -> Result<(), Error> {
+    let target: &Path = Path::new(fname);
+    let temporary: PathBuf = temporary(target);
+    let result: std::io::Result<()> = write_temporary(&temporary, bytes)
+        .and_then(|_| copy_permissions(target, &temporary))
+        .and_then(|_| {
+            std::fs::rename(&temporary, target)
+                .map_err(|error| context(error, "failed to rename the temporary file"))
+        });
+    if let Err(error) = result {
+        let _ = std::fs::remove_file(&temporary);
+        return Err(Error::Write(String::from(fname), error));
+    }
+    if let Err(error) = sync_directory(target) {
+        eprintln!("{} is written, but {}", fname, error);
+    }
+    return Ok(());
+}
+
+/// Gives a hidden temporary file next to the target, unique to this process.
+///
+/// * `target` - Target file.
+fn temporary(target: &Path) -> PathBuf {
+    let name: String = match target.file_name() {
+        Some(name) => name.to_string_lossy().into_owned(),
+        None => String::from("output"),
+    };
+    return target.with_file_name(format!(".{}.{}.tmp", name, std::process::id()));
+}
+
+/// Writes the whole contents to a new file, and syncs it to the disk.
+///
+/// * `temporary` - Temporary file.
+/// * `bytes`     - Contents of the file.
+fn write_temporary(temporary: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let file: std::fs::File = std::fs::File::create(temporary)
+        .map_err(|error| context(error, "failed to create the temporary file"))?;
+    let mut writer: std::io::BufWriter<std::fs::File> = std::io::BufWriter::new(file);
+    writer
+        .write_all(bytes)
+        .map_err(|error| context(error, "failed to write the temporary file"))?;
+    writer
+        .flush()
+        .map_err(|error| context(error, "failed to flush the temporary file"))?;
+    let file: std::fs::File = writer
+        .into_inner()
+        .map_err(|error| context(error.into_error(), "failed to flush the temporary file"))?;
+    return file
+        .sync_all()
+        .map_err(|error| context(error, "failed to sync the temporary file"));
+}
+
+/// Gives the temporary file the permissions of the old target, if any.
+///
+/// * `target`    - Target file.
+/// * `temporary` - Temporary file.
+fn copy_permissions(target: &Path, temporary: &Path) -> std::io::Result<()> {
+    let permissions: std::fs::Permissions = match std::fs::metadata(target) {
+        Ok(metadata) => metadata.permissions(),
+        // a new file keeps the default permissions
+        Err(_) => return Ok(()),
+    };
+    return std::fs::set_permissions(temporary, permissions)
+        .map_err(|error| context(error, "failed to copy the permissions"));
+}
+
+/// Syncs the directory containing the target, so that the rename survives a crash.
+///
+/// * `target` - Target file.
+#[cfg(unix)]
+fn sync_directory(target: &Path) -> std::io::Result<()> {
+    let directory: &Path = match target.parent() {
+        Some(directory) if !directory.as_os_str().is_empty() => directory,
+        _ => Path::new("."),
+    };
+    return std::fs::File::open(directory)
+        .and_then(|directory| directory.sync_all())
+        .map_err(|error| context(error, "failed to sync the directory"));
+}
+
+/// Directories cannot be opened to be synced on the other platforms.
+///
+/// * `target` - Target file.
+#[cfg(not(unix))]
+fn sync_directory(_target: &Path) -> std::io::Result<()> {
+    return Ok(());
+}
+
+/// Prefixes the step at which an I/O operation failed, keeping the kind of the failure.
+///
+/// * `error` - Failure of the operation.
+/// * `step`  - Description of the step.
+fn context(error: std::io::Error, step: &str) -> std::io::Error {
+    return std::io::Error::new(error.kind(), format!("{}: {}", step, error));
+}
+
+#[cfg(test)]
+mod test_atomic {
+    use crate::atomic::write;
+    use crate::error::Error;
+    #[test]
+    fn test_write() -> () {
+        let directory: std::path::PathBuf =
+            std::env::temp_dir().join(format!("mandelbrot-atomic-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let fname: String = directory.join("image.ppm").to_string_lossy().into_owned();
+        // the old contents are replaced as a whole
+        write(&fname, b"old contents").unwrap();
+        write(&fname, b"new").unwrap();
+        assert_eq!(b"new".to_vec(), std::fs::read(&fname).unwrap());
+        // no temporary file is left behind
+        assert_eq!(1, std::fs::read_dir(&directory).unwrap().count());
+        // a failure keeps its cause
+        let missing: String = directory
+            .join("missing/image.ppm")
+            .to_string_lossy()
+            .into_owned();
+        match write(&missing, b"new") {
+            Err(Error::Write(fname, error)) => {
+                assert_eq!(missing, fname);
+                assert_eq!(std::io::ErrorKind::NotFound, error.kind());
+            }
+            _ => panic!("expect a failure to write"),
+        }
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+    #[cfg(unix)]
+    #[test]
+    fn test_permissions() -> () {
+        use std::os::unix::fs::PermissionsExt;
+        let directory: std::path::PathBuf =
+            std::env::temp_dir().join(format!("mandelbrot-permissions-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let fname: String = directory.join("image.ppm").to_string_lossy().into_owned();
+        // the permissions of the old file are kept
+        write(&fname, b"old contents").unwrap();
+        std::fs::set_permissions(&fname, std::fs::Permissions::from_mode(0o640)).unwrap();
+        write(&fname, b"new").unwrap();
+        let mode: u32 = std::fs::metadata(&fname).unwrap().permissions().mode();
+        assert_eq!(0o640, mode & 0o777);
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,165 @@
+#![deny(missing_docs)]
+
+//! Provides a catalogue of well-known locations in the Mandelbrot set.
+//!
+//! In addition to the built-in catalogue, user-maintained bookmarks can be loaded from a file,
+//! which lists one bookmark per line with four whitespace-separated columns:
+//!
+//! ```text
+//! # name        center_x  center_y  span
+//!   my_spiral   -0.7436   0.1318    1.e-4
+//! ```
+//!
+//! Everything after `#` is ignored, and `span` is the width of the view in the complex plane.
+//! User bookmarks take precedence over the built-in ones having the same name.
+
+use crate::coord::Coord;
+use crate::error::{Error, Malformed};
+use crate::text;
+
+/// Stores a named location in the complex plane.
+pub struct Bookmark {
+    /// name used to select this bookmark
+    pub name: String,
+    /// center of the view
+    pub center: Coord<f64>,
+    /// width of the view in the complex plane
+    pub span: f64,
+}
+
+/// Built-in catalogue: name, center (real and imaginary parts), and span.
+const CATALOGUE: [(&str, f64, f64, f64); 8] = [
+    ("whole_set", -0.75, 0., 3.5),
+    ("seahorse_valley", -0.75, 0.1, 0.05),
+    ("elephant_valley", 0.275, 0.006, 0.03),
+    ("triple_spiral_valley", -0.088, 0.654, 0.01),
+    ("scepter_valley", -1.36, 0.005, 0.05),
+    ("needle_minibrot", -1.754_877_666_246_693, 0., 0.04),
+    ("dendrite_at_i", 0., 1., 0.05),
+    (
+        "double_spiral",
+        -0.743_643_887_037_151,
+        0.131_825_904_205_33,
+        5e-6,
+    ),
+];
+
+/// Finds a bookmark by its name.
+///
+/// * `name`  - Name of the bookmark.
+/// * `fname` - Name of the user bookmark file, if given.
+pub fn find(name: &str, fname: &Option<String>) -> Result<Bookmark, Error> {
+    let bookmarks: Vec<Bookmark> = load(fname)?;
+    return match bookmarks.into_iter().find(|bookmark| bookmark.name == name) {
+        Some(bookmark) => Ok(bookmark),
+        None => Err(Error::Argument(format!(
+            "location {} is not found, see the list command",
+            name
+        ))),
+    };
+}
+
+/// Prints all available bookmarks.
+///
+/// * `fname` - Name of the user bookmark file, if given.
+pub fn list(fname: &Option<String>) -> Result<(), Error> {
+    let bookmarks: Vec<Bookmark> = load(fname)?;
+    println!(
+        "{:<24} {:>24} {:>24} {:>10}",
+        "name", "center_x", "center_y", "span"
+    );
+    for bookmark in bookmarks.iter() {
+        println!(
+            "{:<24} {:>+24.15e} {:>+24.15e} {:>10.1e}",
+            bookmark.name, bookmark.center.x, bookmark.center.y, bookmark.span
+        );
+    }
+    return Ok(());
+}
+
+/// Collects the user bookmarks (first) and the built-in ones.
+///
+/// * `fname` - Name of the user bookmark file, if given.
+fn load(fname: &Option<String>) -> Result<Vec<Bookmark>, Error> {
+    let mut bookmarks: Vec<Bookmark> = Vec::new();
+    if let Some(fname) = fname {
+        let contents: String = match std::fs::read_to_string(fname) {
+            Ok(contents) => contents,
+            Err(error) => return Err(Error::Read(String::from(fname), error)),
+        };
+        match parse(&contents) {
+            Ok(user) => bookmarks.extend(user),
+            Err(msg) => return Err(Error::Parse(String::from(fname), msg)),
+        };
+    }
+    for (name, x, y, span) in CATALOGUE.iter() {
+        bookmarks.push(Bookmark {
+            name: String::from(*name),
+            center: Coord { x: *x, y: *y },
+            span: *span,
+        });
+    }
+    return Ok(bookmarks);
+}
+
+/// Converts the contents of a bookmark file to bookmarks.
+///
+/// * `contents` - Whole text of the bookmark file.
+fn parse(contents: &str) -> Result<Vec<Bookmark>, Malformed> {
+    let mut bookmarks: Vec<Bookmark> = Vec::new();
+    for (n, line) in text::lines(contents) {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        if 4 != words.len() {
+            return Err(format!("line {}: expect 4 columns", n + 1).into());
+        }
+        let mut values: [f64; 3] = [0.; 3];
+        for m in 0..3 {
+            values[m] = match words[m + 1].parse::<f64>() {
+                Ok(value) => value,
+                Err(error) => {
+                    return Err(Malformed::caused(
+                        format!("line {}: invalid value {}", n + 1, words[m + 1]),
+                        error,
+                    ))
+                }
+            };
+        }
+        if values[2] <= 0. {
+            return Err(format!("line {}: expect positive span", n + 1).into());
+        }
+        bookmarks.push(Bookmark {
+            name: String::from(words[0]),
+            center: Coord {
+                x: values[0],
+                y: values[1],
+            },
+            span: values[2],
+        });
+    }
+    return Ok(bookmarks);
+}
+
+#[cfg(test)]
+mod test_bookmarks {
+    use crate::bookmarks::{find, parse, Bookmark};
+    #[test]
+    fn test_parse0() -> () {
+        let bookmarks: Vec<Bookmark> = parse("# comment\nmine -0.5 0.25 1.e-3 # note\n\n").unwrap();
+        assert_eq!(1, bookmarks.len());
+        assert_eq!("mine", bookmarks[0].name);
+        assert_eq!(0.25, bookmarks[0].center.y);
+        assert_eq!(1.0e-3, bookmarks[0].span);
+    }
+    #[test]
+    fn test_parse1() -> () {
+        assert!(parse("mine -0.5 0.25\n").is_err());
+        assert!(parse("mine -0.5 0.25 value\n").is_err());
+        assert!(parse("mine -0.5 0.25 0.\n").is_err());
+    }
+    #[test]
+    fn test_find() -> () {
+        let bookmark: Bookmark = find("seahorse_valley", &None).unwrap();
+        assert_eq!(-0.75, bookmark.center.x);
+        assert!(find("nowhere", &None).is_err());
+    }
+}
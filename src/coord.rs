@@ -3,6 +3,7 @@
 //! Defines a generic struct to store two-dimensional information.
 
 /// Stores general two-dimensional information.
+#[derive(Clone, Copy)]
 pub struct Coord<T> {
     /// horizontal
     pub x: T,
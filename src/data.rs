@@ -0,0 +1,387 @@
+#![deny(missing_docs)]
+
+//! Stores the per-pixel results of the recurrence relation, so that an image can be recoloured without recomputation.
+//!
+//! The raw data file consists of a plain-text header of `key=value` lines (see [`crate::manifest`]),
+//! which is closed by an empty line and followed by the channels:
+//!
+//! ```text
+//! # mandelbrot data
+//! version=1
+//! width=1280
+//! height=800
+//! center_x=-0.74364
+//! center_y=0.13182
+//! grid_size=5e-7
+//! rotation=0
+//! max_iter=1024
+//! channels=iter,smooth,distance
+//!
+//! <width x height little-endian values of each channel, row by row from the top>
+//! ```
+//!
+//! * `iter` (u32): the number of iterations to diverge, which is zero for the interior.
+//! * `smooth` (f32): the continuous number of iterations to diverge.
+//! * `distance` (f32): the estimated distance to the Mandelbrot set.
+//!
+//! Files only with the channel `iter` are also read, whose smooth counts are the integer ones.
+//!
+//! When the file name ends with `.npy`, the channel `iter` is written as a [NumPy](https://numpy.org/doc/stable/reference/generated/numpy.lib.format.html) array instead,
+//! whose shape is `(height, width)`; such files are meant for analyses and cannot be recoloured.
+
+use crate::atomic;
+use crate::coord::Coord;
+use crate::error::{Error, Malformed};
+use crate::mandelbrot::{self, Point};
+use crate::manifest::Manifest;
+use crate::options::Options;
+
+/// Magic line at the beginning of a raw data file.
+const MAGIC: &str = "# mandelbrot data\n";
+
+/// Version of the raw data format.
+const VERSION: u32 = 1;
+
+/// Channels written in a raw data file.
+const CHANNELS: &str = "iter,smooth,distance";
+
+/// Stores the results of a render with the view.
+pub struct Data {
+    /// number of pixels in two directions
+    pub resols: Coord<usize>,
+    /// center of the image
+    pub center: Coord<f64>,
+    /// inter-pixel distance
+    pub grid_size: f64,
+    /// rotation angle of the view
+    pub rotation: f64,
+    /// maximum number of iterations
+    pub max_iter: u64,
+    /// result of the recurrence relation for each point
+    pub points: Vec<Point>,
+}
+
+/// Writes the results of a render, in the NPY format when the file name ends with `.npy`.
+///
+/// * `fname`   - Name of the raw data file.
+/// * `options` - Fixed parameters: the number of pixels, the grid size, the rotation.
+/// * `center`  - Center of the image.
+/// * `points`  - Result of the recurrence relation for each point.
+pub fn write(
+    fname: &str,
+    options: &Options,
+    center: &Coord<f64>,
+    points: &[Point],
+) -> Result<(), Error> {
+    let data: Data = Data {
+        resols: options.resols,
+        center: *center,
+        grid_size: options.grid_size,
+        rotation: options.rotation,
+        max_iter: mandelbrot::MAX_ITER,
+        points: points.to_vec(),
+    };
+    let bytes: Vec<u8> = if fname.ends_with(".npy") {
+        encode_npy(&data)
+    } else {
+        encode(&data)
+    };
+    return atomic::write(fname, &bytes);
+}
+
+/// Reads the results of a render.
+///
+/// * `fname` - Name of the raw data file.
+pub fn load(fname: &str) -> Result<Data, Error> {
+    let bytes: Vec<u8> = match std::fs::read(fname) {
+        Ok(bytes) => bytes,
+        Err(error) => return Err(Error::Read(String::from(fname), error)),
+    };
+    return match decode(&bytes) {
+        Ok(data) => Ok(data),
+        Err(msg) => Err(Error::Parse(String::from(fname), msg)),
+    };
+}
+
+/// Colours the results of a past render again, following the current colouring options.
+///
+/// The number of pixels and the view are given by the raw data file.
+///
+/// * `options` - Fixed parameters: output file name, format, colouring.
+/// * `fname`   - Name of the raw data file.
+pub fn recolor(options: &Options, fname: &str) -> Result<(), Error> {
+    let timer: std::time::Instant = std::time::Instant::now();
+    let data: Data = load(fname)?;
+    eprintln!(
+        "recolour {} x {} pixels centered at ({:+.15e}, {:+.15e})",
+        data.resols.x, data.resols.y, data.center.x, data.center.y
+    );
+    let mut options: Options = options.clone();
+    options.resols = data.resols;
+    options.grid_size = data.grid_size;
+    options.rotation = data.rotation;
+    match crate::output::dump(&options, &data.center, &data.points) {
+        Ok(_) => {}
+        Err(error) => return Err(error),
+    };
+    eprintln!(
+        "{} is written in {:.3} s",
+        options.fname,
+        timer.elapsed().as_secs_f64()
+    );
+    return Ok(());
+}
+
+/// Converts the results to the bytes of a raw data file.
+///
+/// * `data` - Results of a render.
+fn encode(data: &Data) -> Vec<u8> {
+    let mut header: Manifest = Manifest {
+        records: Vec::new(),
+    };
+    // f64 values are written in the shortest representation which round-trips
+    header.push("version", VERSION);
+    header.push("width", data.resols.x);
+    header.push("height", data.resols.y);
+    header.push("center_x", data.center.x);
+    header.push("center_y", data.center.y);
+    header.push("grid_size", data.grid_size);
+    header.push("rotation", data.rotation);
+    header.push("max_iter", data.max_iter);
+    header.push("channels", CHANNELS);
+    let mut bytes: Vec<u8> = Vec::from(MAGIC.as_bytes());
+    for (key, value) in header.records.iter() {
+        bytes.extend(format!("{}={}\n", key, value).into_bytes());
+    }
+    bytes.push(b'\n');
+    for point in data.points.iter() {
+        bytes.extend(iter_channel(point).to_le_bytes());
+    }
+    for point in data.points.iter() {
+        bytes.extend((point.smooth as f32).to_le_bytes());
+    }
+    for point in data.points.iter() {
+        bytes.extend((point.distance as f32).to_le_bytes());
+    }
+    return bytes;
+}
+
+/// Converts the bytes of a raw data file to the results.
+///
+/// * `bytes` - Whole contents of the raw data file.
+fn decode(bytes: &[u8]) -> Result<Data, Malformed> {
+    if !bytes.starts_with(MAGIC.as_bytes()) {
+        return Err(String::from("not a raw data file").into());
+    }
+    // the header is closed by an empty line
+    let end: usize = match bytes.windows(2).position(|pair| b"\n\n" == pair) {
+        Some(end) => end + 2,
+        None => return Err(String::from("header is not closed").into()),
+    };
+    let header: Manifest = match std::str::from_utf8(&bytes[0..end]) {
+        Ok(text) => Manifest::parse(text)?,
+        Err(error) => {
+            return Err(Malformed::caused(
+                String::from("header is not a text"),
+                error,
+            ))
+        }
+    };
+    let get = |key: &str| -> Result<&str, Malformed> {
+        return match header.get(key) {
+            Some(value) => Ok(value),
+            None => Err(format!("{} is missing", key).into()),
+        };
+    };
+    let get_f64 = |key: &str| -> Result<f64, Malformed> {
+        return match get(key)?.parse::<f64>() {
+            Ok(value) => Ok(value),
+            Err(error) => Err(Malformed::caused(
+                format!("{} is not a floating number", key),
+                error,
+            )),
+        };
+    };
+    let get_u64 = |key: &str| -> Result<u64, Malformed> {
+        return match get(key)?.parse::<u64>() {
+            Ok(value) => Ok(value),
+            Err(error) => Err(Malformed::caused(
+                format!("{} is not a non-negative integer", key),
+                error,
+            )),
+        };
+    };
+    if VERSION as u64 != get_u64("version")? {
+        return Err(format!("version {} is not supported", get("version")?).into());
+    }
+    let nchannels: usize = match get("channels")? {
+        "iter" => 1,
+        CHANNELS => 3,
+        channels => return Err(format!("channels {} are not supported", channels).into()),
+    };
+    let resols: Coord<usize> = Coord {
+        x: get_u64("width")? as usize,
+        y: get_u64("height")? as usize,
+    };
+    let max_iter: u64 = get_u64("max_iter")?;
+    let nitems: usize = resols.x * resols.y;
+    let body: &[u8] = &bytes[end..];
+    if 4 * nchannels * nitems != body.len() {
+        return Err(format!(
+            "expect {} bytes of channels, found {}",
+            4 * nchannels * nitems,
+            body.len()
+        )
+        .into());
+    }
+    // every channel is stored in 4 bytes
+    let value = |m: usize, n: usize| -> [u8; 4] {
+        let k: usize = 4 * (m * nitems + n);
+        return [body[k], body[k + 1], body[k + 2], body[k + 3]];
+    };
+    let points: Vec<Point> = (0..nitems)
+        .map(|n| {
+            let iter: u64 = u32::from_le_bytes(value(0, n)) as u64;
+            // the interior is marked by zero
+            if 0 == iter {
+                Point {
+                    is_diverged: false,
+                    iter: max_iter,
+                    smooth: max_iter as f64,
+                    distance: 0.,
+                }
+            } else if 1 == nchannels {
+                Point {
+                    is_diverged: true,
+                    iter,
+                    smooth: iter as f64,
+                    distance: 0.,
+                }
+            } else {
+                Point {
+                    is_diverged: true,
+                    iter,
+                    smooth: f32::from_le_bytes(value(1, n)) as f64,
+                    distance: f32::from_le_bytes(value(2, n)) as f64,
+                }
+            }
+        })
+        .collect();
+    return Ok(Data {
+        resols,
+        center: Coord {
+            x: get_f64("center_x")?,
+            y: get_f64("center_y")?,
+        },
+        grid_size: get_f64("grid_size")?,
+        rotation: get_f64("rotation")?,
+        max_iter,
+        points,
+    });
+}
+
+/// Converts the results to the bytes of an NPY file (version 1.0) storing the channel `iter`.
+///
+/// * `data` - Results of a render.
+fn encode_npy(data: &Data) -> Vec<u8> {
+    const MAGIC_NUMBER: &[u8] = b"\x93NUMPY\x01\x00";
+    let mut header: String = format!(
+        "{{'descr': '<u4', 'fortran_order': False, 'shape': ({}, {}), }}",
+        data.resols.y, data.resols.x
+    );
+    // the header is padded by spaces and closed by a newline so that the data are 64-byte aligned
+    let size: usize = (MAGIC_NUMBER.len() + 2 + header.len() + 1).next_multiple_of(64);
+    while MAGIC_NUMBER.len() + 2 + header.len() + 1 < size {
+        header.push(' ');
+    }
+    header.push('\n');
+    let mut bytes: Vec<u8> = Vec::from(MAGIC_NUMBER);
+    bytes.extend((header.len() as u16).to_le_bytes());
+    bytes.extend(header.into_bytes());
+    for point in data.points.iter() {
+        bytes.extend(iter_channel(point).to_le_bytes());
+    }
+    return bytes;
+}
+
+/// Gives the value of the channel `iter` of a point, which is zero for the interior.
+///
+/// * `point` - Result of the recurrence relation.
+fn iter_channel(point: &Point) -> u32 {
+    return if point.is_diverged {
+        point.iter as u32
+    } else {
+        0
+    };
+}
+
+#[cfg(test)]
+mod test_data {
+    use crate::coord::Coord;
+    use crate::data::{decode, encode, encode_npy, Data};
+    use crate::mandelbrot::Point;
+    fn sample() -> Data {
+        return Data {
+            resols: Coord { x: 3, y: 2 },
+            center: Coord {
+                x: -0.743_643_887_037_158_7,
+                y: 0.131_825_904_205_311,
+            },
+            grid_size: 5e-7,
+            rotation: 0.25,
+            max_iter: 1024,
+            points: (0..6)
+                .map(|n| Point {
+                    is_diverged: 0 != n % 4,
+                    iter: if 0 != n % 4 { 100 * n as u64 } else { 1024 },
+                    smooth: if 0 != n % 4 {
+                        100. * n as f64 + 0.25
+                    } else {
+                        1024.
+                    },
+                    distance: if 0 != n % 4 { 0.5 / n as f64 } else { 0. },
+                })
+                .collect(),
+        };
+    }
+    #[test]
+    fn test_round_trip() -> () {
+        let data: Data = sample();
+        let data_: Data = decode(&encode(&data)).unwrap();
+        assert_eq!((3, 2), (data_.resols.x, data_.resols.y));
+        assert_eq!(data.center.x, data_.center.x);
+        assert_eq!(data.center.y, data_.center.y);
+        assert_eq!(data.grid_size, data_.grid_size);
+        assert_eq!(data.rotation, data_.rotation);
+        assert_eq!(data.max_iter, data_.max_iter);
+        for (point, point_) in data.points.iter().zip(data_.points.iter()) {
+            assert_eq!(point.is_diverged, point_.is_diverged);
+            assert_eq!(point.iter, point_.iter);
+            assert_eq!(point.smooth, point_.smooth);
+            assert!((point.distance - point_.distance).abs() <= 1e-7 * point.distance);
+        }
+    }
+    #[test]
+    fn test_decode() -> () {
+        let bytes: Vec<u8> = encode(&sample());
+        assert!(decode(&bytes[1..]).is_err());
+        // truncated channels
+        assert!(decode(&bytes[0..bytes.len() - 1]).is_err());
+    }
+    #[test]
+    fn test_npy() -> () {
+        let bytes: Vec<u8> = encode_npy(&sample());
+        assert_eq!(b"\x93NUMPY\x01\x00", &bytes[0..8]);
+        let size: usize = u16::from_le_bytes([bytes[8], bytes[9]]) as usize;
+        assert_eq!(0, (10 + size) % 64);
+        let header: &str = std::str::from_utf8(&bytes[10..10 + size]).unwrap();
+        assert!(header.contains("'shape': (2, 3)"));
+        assert!(header.ends_with('\n'));
+        let values: Vec<u32> = bytes[10 + size..]
+            .chunks(4)
+            .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()))
+            .collect();
+        // the interior is marked by zero
+        assert_eq!(vec![0, 100, 200, 300, 0, 500], values);
+    }
+}
@@ -0,0 +1,144 @@
+#![deny(missing_docs)]
+
+//! Classifies the failures, each of which terminates the process with its own exit code.
+//!
+//! | class      | exit code | example                                        |
+//! |------------|-----------|------------------------------------------------|
+//! | `Argument` | 2         | unknown key value, inconsistent options        |
+//! | `Parse`    | 3         | malformed manifest, palette, or raw data file  |
+//! | `Read`     | 4         | unreadable manifest or palette file            |
+//! | `Write`    | 5         | full disk, read-only directory                 |
+//! | `NotFound` | 6         | no structure is found, every image is rejected |
+
+/// Failures of the jobs.
+#[derive(Debug)]
+pub enum Error {
+    /// invalid command-line arguments, with the message
+    Argument(String),
+    /// malformed contents of a file, with the file name and the description
+    Parse(String, Malformed),
+    /// failure to read a file, with the file name and the cause
+    Read(String, std::io::Error),
+    /// failure to write a file, with the file name and the cause
+    Write(String, std::io::Error),
+    /// no acceptable image, with the message
+    NotFound(String),
+}
+
+impl Error {
+    /// Gives the exit code of the process terminated by this failure.
+    pub fn exit_code(&self) -> i32 {
+        return match self {
+            Error::Argument(_) => 2,
+            Error::Parse(_, _) => 3,
+            Error::Read(_, _) => 4,
+            Error::Write(_, _) => 5,
+            Error::NotFound(_) => 6,
+        };
+    }
+}
+
+/// Description of malformed contents, keeping the failure to convert a value behind it if any.
+#[derive(Debug)]
+pub struct Malformed {
+    /// message, e.g., with the line number
+    pub msg: String,
+    /// failure to convert a value, e.g., `ParseFloatError`
+    pub cause: Option<Box<dyn std::error::Error + Send + Sync>>,
+}
+
+impl Malformed {
+    /// Describes a value which failed to be converted.
+    ///
+    /// * `msg`   - Message, e.g., with the line number.
+    /// * `cause` - Failure of the conversion.
+    pub fn caused<E: std::error::Error + Send + Sync + 'static>(
+        msg: String,
+        cause: E,
+    ) -> Malformed {
+        return Malformed {
+            msg,
+            cause: Some(Box::new(cause)),
+        };
+    }
+}
+
+impl From<String> for Malformed {
+    fn from(msg: String) -> Malformed {
+        return Malformed { msg, cause: None };
+    }
+}
+
+impl std::fmt::Display for Malformed {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        return write!(f, "{}", self.msg);
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        return match self {
+            Error::Argument(msg) => write!(f, "{}", msg),
+            Error::Parse(fname, malformed) => write!(f, "{}: {}", fname, malformed),
+            Error::Read(fname, error) => write!(f, "failed to read {}: {}", fname, error),
+            Error::Write(fname, error) => write!(f, "failed to write {}: {}", fname, error),
+            Error::NotFound(msg) => write!(f, "{}", msg),
+        };
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        return match self {
+            Error::Parse(_, malformed) => match &malformed.cause {
+                Some(cause) => Some(cause.as_ref()),
+                None => None,
+            },
+            Error::Read(_, error) | Error::Write(_, error) => Some(error),
+            _ => None,
+        };
+    }
+}
+
+#[cfg(test)]
+mod test_error {
+    use crate::error::Error;
+    #[test]
+    fn test_exit_code() -> () {
+        let errors: [Error; 5] = [
+            Error::Argument(String::from("seed: expect positive number")),
+            Error::Parse(
+                String::from("a.ggr"),
+                String::from("line 1: expect GIMP Gradient").into(),
+            ),
+            Error::Read(String::from("a.txt"), std::io::ErrorKind::NotFound.into()),
+            Error::Write(
+                String::from("a.ppm"),
+                std::io::ErrorKind::StorageFull.into(),
+            ),
+            Error::NotFound(String::from("no structure is found inside the domain")),
+        ];
+        // each class has its own code, none of which is the generic one
+        let mut codes: Vec<i32> = errors.iter().map(|error| error.exit_code()).collect();
+        codes.sort();
+        codes.dedup();
+        assert_eq!(5, codes.len());
+        assert!(codes.iter().all(|code| 1 < *code));
+        assert_eq!("a.ggr: line 1: expect GIMP Gradient", errors[1].to_string());
+        assert!(std::error::Error::source(&errors[3]).is_some());
+    }
+    #[test]
+    fn test_parse_source() -> () {
+        use crate::error::Malformed;
+        // the failure to convert a value is kept as the source
+        let cause: std::num::ParseFloatError = "1.x".parse::<f64>().unwrap_err();
+        let error: Error = Error::Parse(
+            String::from("a.csv"),
+            Malformed::caused(String::from("line 2: invalid value 1.x"), cause.clone()),
+        );
+        assert_eq!("a.csv: line 2: invalid value 1.x", error.to_string());
+        let source: &dyn std::error::Error = std::error::Error::source(&error).unwrap();
+        assert_eq!(cause.to_string(), source.to_string());
+        assert!(source.downcast_ref::<std::num::ParseFloatError>().is_some());
+    }
+}
@@ -0,0 +1,302 @@
+#![deny(missing_docs)]
+
+//! Keeps the history of the rendered images to avoid repeats.
+//!
+//! The history is a plain-text file listing one render per line with seven whitespace-separated columns:
+//!
+//! ```text
+//! # time        seed  center_x   center_y  zoom    hash              fname
+//!   1760745600  0     -0.74364   0.13182   1.e4    0f3c5a5a96e1c3f0  image.ppm
+//! ```
+//!
+//! `time` is the UNIX time of the render, `zoom` is [`crate::mandelbrot::BASE_SPAN`] divided by the image width,
+//! and `hash` is a perceptual hash of the image (see [`perceptual_hash`]).
+//! A new image is regarded as a repeat when it is close to a past one in either location or appearance.
+
+use crate::atomic;
+use crate::coord::Coord;
+use crate::error::{Error, Malformed};
+use crate::text;
+
+/// Maximum ratio of the zooms of two images regarded as the same location.
+const ZOOM_RATIO: f64 = 4.;
+
+/// Maximum number of differing bits of the perceptual hashes of two images regarded as the same appearance.
+const HASH_DISTANCE: u32 = 8;
+
+/// Stores a past render.
+pub struct Entry {
+    /// UNIX time of the render
+    pub time: u64,
+    /// random seed of the render
+    pub seed: u64,
+    /// center of the image
+    pub center: Coord<f64>,
+    /// zoom of the image
+    pub zoom: f64,
+    /// perceptual hash of the image
+    pub hash: u64,
+    /// name of the image
+    pub fname: String,
+}
+
+impl Entry {
+    /// Checks whether the two images are close in location or appearance.
+    ///
+    /// Two locations are close when the zooms are comparable and
+    /// the distance between the centers is smaller than the width of the wider image.
+    ///
+    /// * `other` - Another render.
+    pub fn is_similar(&self, other: &Entry) -> bool {
+        let ratio: f64 = f64::max(self.zoom / other.zoom, other.zoom / self.zoom);
+        let span: f64 = crate::mandelbrot::BASE_SPAN / f64::min(self.zoom, other.zoom);
+        let distance: f64 = f64::hypot(
+            self.center.x - other.center.x,
+            self.center.y - other.center.y,
+        );
+        let is_close: bool = ratio < ZOOM_RATIO && distance < span;
+        let is_alike: bool = (self.hash ^ other.hash).count_ones() <= HASH_DISTANCE;
+        return is_close || is_alike;
+    }
+}
+
+/// Computes a 64-bit perceptual hash (difference hash) of an image.
+///
+/// The luminance is averaged onto a 9x8 lattice,
+/// and each bit tells whether the luminance increases to the right neighbour.
+/// Images looking alike have hashes differing only in a few bits.
+///
+/// * `resols` - The number of pixels in two directions.
+/// * `pixels` - RGB values of each pixel.
+pub fn perceptual_hash(resols: &Coord<usize>, pixels: &[u8]) -> u64 {
+    const NX: usize = 9;
+    const NY: usize = 8;
+    let mut sums: [[f64; NX]; NY] = [[0.; NX]; NY];
+    let mut counts: [[usize; NX]; NY] = [[0; NX]; NY];
+    for j in 0..resols.y {
+        for i in 0..resols.x {
+            let n: usize = j * resols.x + i;
+            let luminance: f64 = 0.299 * pixels[3 * n] as f64
+                + 0.587 * pixels[3 * n + 1] as f64
+                + 0.114 * pixels[3 * n + 2] as f64;
+            sums[j * NY / resols.y][i * NX / resols.x] += luminance;
+            counts[j * NY / resols.y][i * NX / resols.x] += 1;
+        }
+    }
+    let mut hash: u64 = 0;
+    for j in 0..NY {
+        for i in 0..NX - 1 {
+            // empty cells, appearing for tiny images, are regarded as black
+            let left: f64 = sums[j][i] / usize::max(1, counts[j][i]) as f64;
+            let right: f64 = sums[j][i + 1] / usize::max(1, counts[j][i + 1]) as f64;
+            hash = (hash << 1) | (left < right) as u64;
+        }
+    }
+    return hash;
+}
+
+/// Reads the past renders, which are empty if the file does not exist yet.
+///
+/// * `fname` - Name of the history file.
+pub fn load(fname: &str) -> Result<Vec<Entry>, Error> {
+    if !std::path::Path::new(fname).exists() {
+        return Ok(Vec::new());
+    }
+    let contents: String = match std::fs::read_to_string(fname) {
+        Ok(contents) => contents,
+        Err(error) => return Err(Error::Read(String::from(fname), error)),
+    };
+    return match parse(&contents) {
+        Ok(entries) => Ok(entries),
+        Err(msg) => Err(Error::Parse(String::from(fname), msg)),
+    };
+}
+
+/// Appends a render to the history.
+///
+/// * `fname` - Name of the history file.
+/// * `entry` - New render.
+pub fn append(fname: &str, entry: &Entry) -> Result<(), Error> {
+    let mut entries: Vec<Entry> = load(fname)?;
+    entries.push(Entry {
+        time: entry.time,
+        seed: entry.seed,
+        center: entry.center,
+        zoom: entry.zoom,
+        hash: entry.hash,
+        fname: entry.fname.clone(),
+    });
+    return write(fname, &entries);
+}
+
+/// Prints all past renders.
+///
+/// * `fname` - Name of the history file.
+pub fn list(fname: &str) -> Result<(), Error> {
+    let entries: Vec<Entry> = load(fname)?;
+    println!(
+        "{:>12} {:>8} {:>24} {:>24} {:>10} {:>16} fname",
+        "time", "seed", "center_x", "center_y", "zoom", "hash"
+    );
+    for entry in entries.iter() {
+        println!(
+            "{:>12} {:>8} {:>+24.15e} {:>+24.15e} {:>10.3e} {:016x} {}",
+            entry.time,
+            entry.seed,
+            entry.center.x,
+            entry.center.y,
+            entry.zoom,
+            entry.hash,
+            entry.fname
+        );
+    }
+    return Ok(());
+}
+
+/// Drops the past renders except the latest ones.
+///
+/// * `fname` - Name of the history file.
+/// * `keep`  - Number of the latest renders to be kept.
+pub fn prune(fname: &str, keep: usize) -> Result<(), Error> {
+    let mut entries: Vec<Entry> = load(fname)?;
+    let nitems: usize = entries.len();
+    if nitems <= keep {
+        eprintln!("{} renders are kept, nothing to prune", nitems);
+        return Ok(());
+    }
+    entries.drain(0..nitems - keep);
+    eprintln!("{} out of {} renders are pruned", nitems - keep, nitems);
+    return write(fname, &entries);
+}
+
+/// Writes the renders to a file.
+///
+/// * `fname`   - Name of the history file.
+/// * `entries` - All renders to be kept.
+fn write(fname: &str, entries: &[Entry]) -> Result<(), Error> {
+    let mut contents: String = String::from("# time seed center_x center_y zoom hash fname\n");
+    for entry in entries.iter() {
+        // f64 values are written in the shortest representation which round-trips
+        contents.push_str(&format!(
+            "{} {} {} {} {} {:016x} {}\n",
+            entry.time,
+            entry.seed,
+            entry.center.x,
+            entry.center.y,
+            entry.zoom,
+            entry.hash,
+            entry.fname
+        ));
+    }
+    return atomic::write(fname, contents.as_bytes());
+}
+
+/// Converts the contents of a history file to renders.
+///
+/// * `contents` - Whole text of the history file.
+fn parse(contents: &str) -> Result<Vec<Entry>, Malformed> {
+    let mut entries: Vec<Entry> = Vec::new();
+    for (n, line) in text::lines(contents) {
+        // the last column may contain spaces
+        let mut words: Vec<&str> = Vec::new();
+        let mut rest: &str = line;
+        while words.len() < 6 {
+            match rest.split_once(char::is_whitespace) {
+                Some((word, remainder)) => {
+                    words.push(word);
+                    rest = remainder.trim_start();
+                }
+                None => break,
+            };
+        }
+        words.push(rest);
+        if 7 != words.len() {
+            return Err(format!("line {}: expect 7 columns", n + 1).into());
+        }
+        let invalid = |word: &str| -> String {
+            return format!("line {}: invalid value {}", n + 1, word);
+        };
+        let integers: Vec<u64> = match words[0..2]
+            .iter()
+            .map(|word| word.parse::<u64>())
+            .collect::<Result<Vec<u64>, _>>()
+        {
+            Ok(integers) => integers,
+            Err(error) => return Err(Malformed::caused(invalid(&words[0..2].join(" ")), error)),
+        };
+        let reals: Vec<f64> = match words[2..5]
+            .iter()
+            .map(|word| word.parse::<f64>())
+            .collect::<Result<Vec<f64>, _>>()
+        {
+            Ok(reals) => reals,
+            Err(error) => return Err(Malformed::caused(invalid(&words[2..5].join(" ")), error)),
+        };
+        let hash: u64 = match u64::from_str_radix(words[5], 16) {
+            Ok(hash) => hash,
+            Err(error) => return Err(Malformed::caused(invalid(words[5]), error)),
+        };
+        entries.push(Entry {
+            time: integers[0],
+            seed: integers[1],
+            center: Coord {
+                x: reals[0],
+                y: reals[1],
+            },
+            zoom: reals[2],
+            hash,
+            fname: String::from(words[6]),
+        });
+    }
+    return Ok(entries);
+}
+
+#[cfg(test)]
+mod test_history {
+    use crate::coord::Coord;
+    use crate::history::{parse, perceptual_hash, Entry};
+    fn entry(x: f64, zoom: f64, hash: u64) -> Entry {
+        return Entry {
+            time: 0,
+            seed: 0,
+            center: Coord { x, y: 0. },
+            zoom,
+            hash,
+            fname: String::from("image.ppm"),
+        };
+    }
+    #[test]
+    fn test_parse() -> () {
+        let entries: Vec<Entry> =
+            parse("# comment\n1760745600 3  -0.5 0.25 1e4 00000000000000ff my image.ppm\n\n")
+                .unwrap();
+        assert_eq!(1, entries.len());
+        assert_eq!(3, entries[0].seed);
+        assert_eq!(0.25, entries[0].center.y);
+        assert_eq!(255, entries[0].hash);
+        assert_eq!("my image.ppm", entries[0].fname);
+        assert!(parse("1760745600 3 -0.5 0.25 1e4\n").is_err());
+        assert!(parse("1760745600 3 -0.5 0.25 1e4 xyz image.ppm\n").is_err());
+    }
+    #[test]
+    fn test_is_similar() -> () {
+        // close in location
+        assert!(entry(0., 1e4, 0).is_similar(&entry(1e-4, 2e4, u64::MAX)));
+        // zoomed much deeper
+        assert!(!entry(0., 1e4, 0).is_similar(&entry(1e-4, 1e5, u64::MAX)));
+        // far away
+        assert!(!entry(0., 1e4, 0).is_similar(&entry(1e-3, 1e4, u64::MAX)));
+        // far away but looking alike
+        assert!(entry(0., 1e4, 0).is_similar(&entry(1e-3, 1e4, 0xff)));
+    }
+    #[test]
+    fn test_perceptual_hash() -> () {
+        // brightness increasing to the right everywhere
+        let resols: Coord<usize> = Coord { x: 18, y: 8 };
+        let pixels: Vec<u8> = (0..18 * 8 * 3).map(|n| (n / 3 % 18 * 10) as u8).collect();
+        assert_eq!(u64::MAX, perceptual_hash(&resols, &pixels));
+        // flat
+        let pixels: Vec<u8> = vec![128u8; 18 * 8 * 3];
+        assert_eq!(0, perceptual_hash(&resols, &pixels));
+    }
+}
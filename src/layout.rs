@@ -0,0 +1,250 @@
+#![deny(missing_docs)]
+
+//! Splits the found view into one image per screen of a multi-screen setup.
+//!
+//! A layout file lists one screen per line with six whitespace-separated columns:
+//!
+//! ```text
+//! # width  height  size_x  size_y  x    y
+//!   2560   1440    597.    336.    0.   0.
+//!   1920   1080    477.    268.    617. 34.
+//! ```
+//!
+//! Everything after `#` is ignored.
+//! The first two columns give the number of pixels,
+//! and the others give the physical size and the position of the top-left corner of the screen
+//! in any common length unit (e.g., millimetres), whose y axis points downwards.
+//! The gaps between the screens (e.g., bezels) are thus given by their positions.
+//!
+//! The layout is fitted to the view searched at `width` and `height`,
+//! and each screen shows its own part with the same physical scale regardless of its pixel density,
+//! so that the screens look like a single window onto the complex plane.
+//! The colours are normalised over all screens, and the palettes depending on the pixel position
+//! are laid out around the center of the whole layout, for the same reason.
+//! Each image is written to the name with the index of the screen appended, e.g., `image_0.ppm`,
+//! together with a manifest recording the searched view and the layout,
+//! since a screen cannot be reproduced without the others.
+
+use crate::coord::Coord;
+use crate::error::{Error, Malformed};
+use crate::mandelbrot::{self, Point};
+use crate::manifest::{self, Manifest};
+use crate::options::Options;
+use crate::output::{self, Part};
+use crate::text;
+use std::time::{Duration, Instant};
+
+/// Stores a screen of the layout.
+pub struct Screen {
+    /// number of pixels in two directions
+    pub resols: Coord<usize>,
+    /// physical size
+    pub size: Coord<f64>,
+    /// physical position of the top-left corner
+    pub position: Coord<f64>,
+}
+
+/// Main function, which renders the image of each screen described by the given layout file.
+///
+/// * `options`     - Configuration parameters of the searched view.
+/// * `center`      - Center of the searched view.
+/// * `time_search` - Time spent to find the center, which is recorded in each manifest.
+/// * `fname`       - Name of the layout file.
+pub fn execute(
+    options: &Options,
+    center: &Coord<f64>,
+    time_search: Duration,
+    fname: &str,
+) -> Result<(), Error> {
+    let screens: Vec<Screen> = load(fname)?;
+    let corners: Vec<(Coord<usize>, [Coord<f64>; 2])> = screens
+        .iter()
+        .map(|screen| {
+            let upper: Coord<f64> = Coord {
+                x: screen.position.x + screen.size.x,
+                y: screen.position.y + screen.size.y,
+            };
+            (screen.resols, [screen.position, upper])
+        })
+        .collect();
+    let views: Vec<(Coord<f64>, f64)> = mandelbrot::split_view(options, center, &corners);
+    // solve all screens first to normalise the colours over them
+    let timer: Instant = Instant::now();
+    let mut parts: Vec<(Options, Coord<f64>, Vec<Point>)> = Vec::with_capacity(screens.len());
+    let (lower, upper): (Coord<f64>, Coord<f64>) = bounding_box(&corners);
+    for (index, (screen, (screen_center, grid_size))) in screens.iter().zip(views).enumerate() {
+        let mut view: Options = options.clone();
+        view.resols = screen.resols;
+        view.grid_size = grid_size;
+        view.fname = output::suffixed_fname(&options.fname, &index.to_string())?;
+        // the raw data is kept only for the searched view
+        view.data = None;
+        let points: Vec<Point> = mandelbrot::solve_recurrence_relation(&view, &screen_center)?;
+        parts.push((view, screen_center, points));
+    }
+    let time_solve: Duration = timer.elapsed();
+    let whole: Vec<Point> = parts
+        .iter()
+        .flat_map(|(_, _, points)| points.iter().copied())
+        .collect();
+    for ((view, screen_center, points), (_, [lower_s, upper_s])) in parts.iter().zip(corners.iter())
+    {
+        // corners of the screen in the bounding box scaled to [0:1]
+        let scale = |value: f64, lower_b: f64, upper_b: f64| -> f64 {
+            return (value - lower_b) / (upper_b - lower_b);
+        };
+        let part: Part = Part {
+            corners: [
+                Coord {
+                    x: scale(lower_s.x, lower.x, upper.x),
+                    y: scale(lower_s.y, lower.y, upper.y),
+                },
+                Coord {
+                    x: scale(upper_s.x, lower.x, upper.x),
+                    y: scale(upper_s.y, lower.y, upper.y),
+                },
+            ],
+            whole: &whole,
+        };
+        let timer: Instant = Instant::now();
+        output::dump_part(view, screen_center, points, &part)?;
+        let time_output: Duration = timer.elapsed();
+        eprintln!(
+            "{}x{} is written to {}",
+            view.resols.x, view.resols.y, view.fname
+        );
+        let manifest: Manifest = Manifest::new(
+            options,
+            center,
+            &[
+                ("search", time_search),
+                ("solve", time_solve),
+                ("output", time_output),
+            ],
+        );
+        manifest.write(&manifest::sidecar_fname(&view.fname))?;
+    }
+    return Ok(());
+}
+
+/// Gives the lower and upper corners of the bounding box of the screens.
+///
+/// * `corners` - Number of pixels and physical lower and upper corners of each screen.
+fn bounding_box(corners: &[(Coord<usize>, [Coord<f64>; 2])]) -> (Coord<f64>, Coord<f64>) {
+    let mut lower: Coord<f64> = corners[0].1[0];
+    let mut upper: Coord<f64> = corners[0].1[1];
+    for (_, [lower_s, upper_s]) in corners.iter() {
+        lower.x = f64::min(lower.x, lower_s.x);
+        lower.y = f64::min(lower.y, lower_s.y);
+        upper.x = f64::max(upper.x, upper_s.x);
+        upper.y = f64::max(upper.y, upper_s.y);
+    }
+    return (lower, upper);
+}
+
+/// Loads and validates a layout file.
+///
+/// * `fname` - Name of the layout file.
+fn load(fname: &str) -> Result<Vec<Screen>, Error> {
+    let contents: String = match std::fs::read_to_string(fname) {
+        Ok(contents) => contents,
+        Err(error) => return Err(Error::Read(String::from(fname), error)),
+    };
+    return match parse(&contents) {
+        Ok(screens) => Ok(screens),
+        Err(msg) => Err(Error::Parse(String::from(fname), msg)),
+    };
+}
+
+/// Converts the contents of a layout file to a list of screens.
+///
+/// * `contents` - Whole text of the layout file.
+fn parse(contents: &str) -> Result<Vec<Screen>, Malformed> {
+    // pixel pitches of the two directions may differ by this fraction
+    const TOLERANCE: f64 = 0.02;
+    let mut screens: Vec<Screen> = Vec::new();
+    for (n, line) in text::lines(contents) {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        if 6 != words.len() {
+            return Err(format!("line {}: expect 6 columns", n + 1).into());
+        }
+        let mut resols: [usize; 2] = [0; 2];
+        for m in 0..2 {
+            resols[m] = match words[m].parse::<usize>() {
+                Ok(value) if 0 < value => value,
+                Ok(_) => {
+                    return Err(format!("line {}: expect positive number of pixels", n + 1).into())
+                }
+                Err(error) => {
+                    return Err(Malformed::caused(
+                        format!("line {}: expect positive number of pixels", n + 1),
+                        error,
+                    ));
+                }
+            };
+        }
+        let mut values: [f64; 4] = [0.; 4];
+        for m in 0..4 {
+            values[m] = match words[m + 2].parse::<f64>() {
+                Ok(value) => value,
+                Err(error) => {
+                    return Err(Malformed::caused(
+                        format!("line {}: invalid value {}", n + 1, words[m + 2]),
+                        error,
+                    ))
+                }
+            };
+        }
+        if values[0] <= 0. || values[1] <= 0. {
+            return Err(format!("line {}: expect positive size", n + 1).into());
+        }
+        let pitches: Coord<f64> = Coord {
+            x: values[0] / resols[0] as f64,
+            y: values[1] / resols[1] as f64,
+        };
+        if TOLERANCE * pitches.x < (pitches.x - pitches.y).abs() {
+            return Err(format!("line {}: expect square pixels", n + 1).into());
+        }
+        screens.push(Screen {
+            resols: Coord {
+                x: resols[0],
+                y: resols[1],
+            },
+            size: Coord {
+                x: values[0],
+                y: values[1],
+            },
+            position: Coord {
+                x: values[2],
+                y: values[3],
+            },
+        });
+    }
+    if screens.is_empty() {
+        return Err(String::from("no screen is found").into());
+    }
+    return Ok(screens);
+}
+
+#[cfg(test)]
+mod test_layout {
+    use crate::layout::{parse, Screen};
+    #[test]
+    fn test_parse0() -> () {
+        let screens: Vec<Screen> =
+            parse("# comment\n2560 1440 597. 336. 0. 0.\n\n1920 1080 477. 268. 617. 34. # right\n")
+                .unwrap();
+        assert_eq!(2, screens.len());
+        assert_eq!(1920, screens[1].resols.x);
+        assert_eq!(617., screens[1].position.x);
+    }
+    #[test]
+    fn test_parse1() -> () {
+        assert!(parse("").is_err());
+        assert!(parse("2560 1440 597. 336. 0.\n").is_err());
+        assert!(parse("0 1440 597. 336. 0. 0.\n").is_err());
+        assert!(parse("2560 1440 0. 336. 0. 0.\n").is_err());
+        // non-square pixels
+        assert!(parse("2560 1440 597. 597. 0. 0.\n").is_err());
+    }
+}
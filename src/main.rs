@@ -5,11 +5,25 @@
 //! <img class="thumbnail" src="../static.files/image.jpg" onerror="this.onerror=null; this.remove();" alt="" width="50%" />  
 //! </div>  
 
+mod animation;
+mod atomic;
+mod bookmarks;
 mod coord;
+mod data;
+mod error;
+mod history;
+mod layout;
 mod mandelbrot;
+mod manifest;
 mod options;
 mod output;
+mod palette;
+mod quality;
 mod random;
+mod sizes;
+#[cfg(test)]
+mod test_util;
+mod text;
 
 /// Entrypoint of this crate.  
 ///
@@ -27,6 +41,8 @@ mod random;
 /// Extracting a fairly complex structure from the Mandelbrot set (or other fractals) is not a simple task.  
 /// This function takes care of such *nice* extraction.
 ///   
+/// This step is skipped when the view is given explicitly.
+///   
 /// See also: [`mandelbrot::find_center()`].
 ///
 /// # Solve recurrence relation
@@ -40,31 +56,246 @@ mod random;
 ///
 /// # Generate an image
 ///
-/// In the final step, we convert the pixel information into a simple image in the [Portable Any Map](https://netpbm.sourceforge.net/doc/ppm.html) format.  
+/// In the final step, we convert the pixel information into a simple image in the [Portable Any Map](https://netpbm.sourceforge.net/doc/ppm.html) format,
+/// or in the [Portable Network Graphics](https://www.w3.org/TR/png/) format when the file name ends with `.png`.  
+///
+/// Before written, the image is scored by its quality,
+/// and the search is retried with the next seed if it is too boring
+/// or too close to a past render recorded in the history file.
+///
+/// See also: [`output::pixelise()`], [`quality::Scores`], and [`output::write_image()`].
+///
+/// # Record a manifest
+///
+/// Finally, the parameters, the image center, and the timings are recorded next to the image,
+/// which can be given to `--from_manifest` to reproduce the image.
+///
+/// When a raw data file is given, the per-pixel results are also kept
+/// so that the image can be recoloured later without recomputation.
+///
+/// See also: [`manifest::Manifest`] and [`data::write()`].
+///
+/// # Commands
+///
+/// When a command is given as the first argument, the corresponding job is done instead.
+/// `list` prints the catalogue of locations,
+/// while `history` and `prune` print and shrink the history of the past renders.
+/// `recolor` colours the results stored in a raw data file again,
+/// and `info` prints the metadata embedded in an image.
+///
+/// See also: [`bookmarks::list()`], [`history::list()`], [`history::prune()`], [`data::recolor()`], and [`output::info()`].
+///
+/// # Animation
 ///
-/// See also: [`output::execute()`].
+/// When a keyframe file is given, the second step is skipped and a sequence of images is rendered instead,
+/// whose views are interpolated between the keyframes.
+///
+/// See also: [`animation::execute()`].
 pub fn main() -> () {
     use coord::Coord;
+    use error::Error;
+    use history::Entry;
     use mandelbrot::Point;
+    use manifest::Manifest;
     use options::Options;
+    use output::Image;
+    use quality::Scores;
+    use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
     // load command-line arguments to set options
-    let options: Options = match options::init() {
+    let mut options: Options = match options::init() {
         Ok(options) => options,
-        Err(_) => std::process::exit(1),
+        Err(error) => abort(error),
+    };
+    // jobs other than rendering
+    // the history file and the raw data file are ensured to be given for the commands using it
+    let history_fname: String = options.history.clone().unwrap_or_default();
+    let data_fname: String = options.data.clone().unwrap_or_default();
+    let result: Option<Result<(), Error>> = match options.command {
+        options::Command::Render => None,
+        options::Command::List => Some(bookmarks::list(&options.bookmarks)),
+        options::Command::History => Some(history::list(&history_fname)),
+        options::Command::Prune => Some(history::prune(&history_fname, options.keep)),
+        options::Command::Recolor => Some(data::recolor(&options, &data_fname)),
+        options::Command::Info => Some(output::info(&options.fname)),
+    };
+    match result {
+        None => {}
+        Some(Ok(_)) => return,
+        Some(Err(error)) => abort(error),
     };
+    // render scripted frames instead of a single image
+    if let Some(keyframes) = &options.keyframes {
+        match animation::execute(&options, keyframes) {
+            Ok(_) => return,
+            Err(error) => abort(error),
+        };
+    }
     // find a nice image center so that the resulting image has something to display
-    let center: Coord<f64> = match mandelbrot::find_center(&options) {
-        Ok(center) => center,
-        Err(_) => std::process::exit(1),
+    // the search is skipped when the view is given explicitly
+    // when the image is rejected by its quality, the search is retried with the next seed
+    // renders close to the past ones are also rejected
+    let past: Vec<Entry> = match &options.history {
+        Some(history) => match history::load(history) {
+            Ok(past) => past,
+            Err(error) => abort(error),
+        },
+        None => Vec::new(),
     };
-    // obtain the number of iterations to diverge
-    let points: Vec<Point> = match mandelbrot::solve_recurrence_relation(&options, &center) {
-        Ok(points) => points,
-        Err(_) => std::process::exit(1),
+    // the image of the found view is not written when rendered at each size or on each screen,
+    // and is solved only when it is judged or kept in the raw data file
+    let is_written: bool = options.sizes.is_empty() && options.layout.is_none();
+    let is_judged: bool = quality::is_judged(&options) || options.history.is_some();
+    let is_solved: bool = is_written || is_judged || options.data.is_some();
+    let base_seed: u64 = options.seed;
+    let base_grid_size: f64 = options.grid_size;
+    let mut attempt: usize = 0;
+    let (center, render, time_search, time_solve, time_output) = loop {
+        let timer: Instant = Instant::now();
+        let center: Coord<f64> = match options.center {
+            Some(center) => center,
+            None => match mandelbrot::find_center(&options) {
+                Ok((center, grid_size)) => {
+                    options.grid_size = grid_size;
+                    center
+                }
+                Err(error) => abort(error),
+            },
+        };
+        let time_search: Duration = timer.elapsed();
+        if !is_solved {
+            break (center, None, time_search, Duration::ZERO, Duration::ZERO);
+        }
+        // obtain the number of iterations to diverge
+        let timer: Instant = Instant::now();
+        let points: Vec<Point> = match mandelbrot::solve_recurrence_relation(&options, &center) {
+            Ok(points) => points,
+            Err(error) => abort(error),
+        };
+        let time_solve: Duration = timer.elapsed();
+        // convert the results of the recurrence relation to an image, which is written once accepted
+        let timer: Instant = Instant::now();
+        let image: Image = match output::pixelise(&options, &center, &points) {
+            Ok(image) => image,
+            Err(error) => abort(error),
+        };
+        let pixels: Vec<u8> = image.rgb8();
+        let time_output: Duration = timer.elapsed();
+        // judge the quality of the image
+        let scores: Scores = Scores::new(&options.resols, &points, &pixels);
+        scores.print();
+        let mut failures: Vec<&str> = scores.failures(&options);
+        // compare with the past renders
+        let entry: Entry = Entry {
+            time: match SystemTime::now().duration_since(UNIX_EPOCH) {
+                Ok(time) => time.as_secs(),
+                Err(_) => 0,
+            },
+            seed: options.seed,
+            center,
+            zoom: mandelbrot::BASE_SPAN / (options.grid_size * options.resols.x as f64),
+            hash: history::perceptual_hash(&options.resols, &pixels),
+            fname: options.fname.clone(),
+        };
+        if let Some(similar) = past.iter().find(|other| entry.is_similar(other)) {
+            eprintln!(
+                "the image resembles {} rendered with seed {}",
+                similar.fname, similar.seed
+            );
+            failures.push("repeat");
+        }
+        if failures.is_empty() {
+            break (
+                center,
+                Some((points, image, scores, entry)),
+                time_search,
+                time_solve,
+                time_output,
+            );
+        }
+        eprintln!("the image is rejected due to: {}", failures.join(", "));
+        if options.center.is_some() {
+            eprintln!("the view is given explicitly, keep it anyway");
+            break (
+                center,
+                Some((points, image, scores, entry)),
+                time_search,
+                time_solve,
+                time_output,
+            );
+        }
+        if options.retries <= attempt {
+            abort(Error::NotFound(format!(
+                "no acceptable image is found after {} retries, try another random seed or relax the thresholds",
+                attempt
+            )));
+        }
+        attempt += 1;
+        options.seed = base_seed.wrapping_add(attempt as u64);
+        options.grid_size = base_grid_size;
+        eprintln!("retry with seed {}", options.seed);
     };
-    // convert the results of the recurrence relation to an image
-    match output::execute(&options, points) {
+    // write the image, or render the same center at each size or on each screen instead
+    let timer: Instant = Instant::now();
+    let result: Result<(), Error> = if let Some(layout) = &options.layout {
+        layout::execute(&options, &center, time_search, layout)
+    } else if !options.sizes.is_empty() {
+        sizes::execute(&options, &center, time_search)
+    } else if let Some((_, image, _, _)) = &render {
+        output::write_image(&options, image)
+    } else {
+        Ok(())
+    };
+    match result {
         Ok(_) => {}
-        Err(_) => std::process::exit(1),
+        Err(error) => abort(error),
+    };
+    let time_output: Duration = time_output + timer.elapsed();
+    // keep the results to recolour them later
+    if let (Some(data), Some((points, _, _, _))) = (&options.data, &render) {
+        match data::write(data, &options, &center, points) {
+            Ok(_) => {}
+            Err(error) => abort(error),
+        };
+    }
+    // remember the render to avoid repeats
+    if let (Some(history), Some((_, _, _, entry))) = (&options.history, &render) {
+        match history::append(history, entry) {
+            Ok(_) => {}
+            Err(error) => abort(error),
+        };
+    }
+    // record what is needed to reproduce the image
+    // the image streamed to the standard output carries the metadata by itself
+    // each size or screen has been recorded with its image
+    let scores: &Scores = match &render {
+        Some((_, _, scores, _)) if is_written && output::STDOUT != options.fname => scores,
+        _ => return,
     };
+    let mut manifest: Manifest = Manifest::new(
+        &options,
+        &center,
+        &[
+            ("search", time_search),
+            ("solve", time_solve),
+            ("output", time_output),
+        ],
+    );
+    manifest.push("quality_interior", scores.interior);
+    manifest.push("quality_colour_variance", scores.colour_variance);
+    manifest.push("quality_edge_density", scores.edge_density);
+    manifest.push("quality_histogram_spread", scores.histogram_spread);
+    match manifest.write(&manifest::sidecar_fname(&options.fname)) {
+        Ok(_) => {}
+        Err(error) => abort(error),
+    };
+}
+
+/// Reports a failure and terminates the process with the exit code of its class.
+///
+/// See also: [`error::Error::exit_code()`].
+///
+/// * `error` - Failure of a job.
+fn abort(error: error::Error) -> ! {
+    eprintln!("ERROR: {}", error);
+    std::process::exit(error.exit_code());
 }
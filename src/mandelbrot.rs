@@ -2,26 +2,64 @@
 
 //! Plays the central role in this crate.
 
+mod anneal;
 mod common;
 mod find_center;
+mod sampling;
 mod solve;
 
+pub use solve::MAX_ITER;
+
+/// Width of the complex plane covered by an image whose zoom is unity,
+/// i.e., the zoom of an image is this value divided by its width.
+pub const BASE_SPAN: f64 = 4.;
+
 use crate::coord::Coord;
-use crate::options::Options;
+use crate::error::Error;
+use crate::options::{Options, Search};
 
 /// Stores the result of the recurrence relations of the Mandelbrot set
+#[derive(Clone, Copy)]
 pub struct Point {
     /// flag to tell the convergence of the recurrence relation
     pub is_diverged: bool,
     /// number of iterations to diverge
     pub iter: u64,
+    /// continuous number of iterations to diverge, which equals `iter` for the interior
+    pub smooth: f64,
+    /// estimated distance to the Mandelbrot set, which is zero for the interior
+    pub distance: f64,
 }
 
 /// Extracts a fairly complex structure from the Mandelbrot set.
 ///   
-/// See also: [`crate::mandelbrot::find_center::find_center`].
-pub fn find_center(options: &Options) -> Result<Coord<f64>, ()> {
-    return find_center::find_center(options);
+/// Returns the center and the grid size of the final image.
+///   
+/// See also: [`crate::mandelbrot::find_center::find_center`] and [`crate::mandelbrot::anneal::find_center`].
+pub fn find_center(options: &Options) -> Result<(Coord<f64>, f64), Error> {
+    return match options.search {
+        Search::Quadrant => find_center::find_center(options),
+        Search::Anneal => anneal::find_center(options),
+    };
+}
+
+/// Splits the view of an image into the views of several screens.
+///   
+/// Returns the center and the grid size of each screen.
+///   
+/// See also: [`crate::mandelbrot::common::split_view`].
+pub fn split_view(
+    options: &Options,
+    center: &Coord<f64>,
+    screens: &[(Coord<usize>, [Coord<f64>; 2])],
+) -> Vec<(Coord<f64>, f64)> {
+    return common::split_view(
+        &options.resols,
+        center,
+        options.grid_size,
+        options.rotation,
+        screens,
+    );
 }
 
 /// Solves the recurrence relation of the Mandelbrot set.  
@@ -31,6 +69,14 @@ pub fn find_center(options: &Options) -> Result<Coord<f64>, ()> {
 /// 2. how many iterations are needed if the point diverges.
 ///   
 /// See also: [`crate::mandelbrot::solve::solve`].
-pub fn solve_recurrence_relation(options: &Options, center: &Coord<f64>) -> Result<Vec<Point>, ()> {
-    return Ok(solve::solve(&options.resols, center, options.grid_size));
+pub fn solve_recurrence_relation(
+    options: &Options,
+    center: &Coord<f64>,
+) -> Result<Vec<Point>, Error> {
+    return Ok(solve::solve(
+        &options.resols,
+        center,
+        options.grid_size,
+        options.rotation,
+    ));
 }
@@ -0,0 +1,167 @@
+#![deny(missing_docs)]
+
+//! Detects complex structures in the Mandelbrot set by simulated annealing.
+//!
+//! Unlike [`crate::mandelbrot::find_center`], which always zooms into one of the four quadrants,
+//! the center and the zoom are perturbed randomly,
+//! so that structures straddling the middle of a window can also be reached.
+
+use crate::coord::Coord;
+use crate::error::Error;
+use crate::mandelbrot::{common, sampling, solve, Point};
+use crate::options::Options;
+use crate::random::Random;
+
+/// Initial temperature, which is compared with the relative change of the complexity.
+const INITIAL_TEMPERATURE: f64 = 0.5;
+
+/// Lower limit of the temperature, which keeps the search from being trapped.
+const MIN_TEMPERATURE: f64 = 0.05;
+
+/// Rate at which the temperature decreases for each proposal.
+const COOLING: f64 = 0.97;
+
+/// Maximum move of the center for each proposal, relative to the window size.
+const MAX_MOVE: f64 = 0.25;
+
+/// Range of the zoom-in rate for each proposal.
+const ZOOM_RANGE: [f64; 2] = [0.6, 0.95];
+
+/// Maximum number of proposals, after which the search gives up.
+const MAX_PROPOSALS: usize = 2048;
+
+/// Finding a nice image center by simulated annealing.
+///
+/// Starting from the same initial condition as [`crate::mandelbrot::find_center`],
+/// a new center and a new zoom are proposed, which are accepted
+/// when the complexity increases or otherwise with the Metropolis probability.
+/// The proposals continue until the grid size reaches the target.
+/// Returns the center and the grid size of the final image.
+///
+/// * `options` - Fixed parameters which control the overall behaviour.
+pub fn find_center(options: &Options) -> Result<(Coord<f64>, f64), Error> {
+    const DEBUG_ON: bool = false;
+    // initialise random number generator
+    let mut rng: Random = Random::new(options.seed);
+    // consider rough image for faster check
+    let resols: Coord<usize> = common::search_resols(&options.resols);
+    let region: &[Coord<f64>; 2] = &options.region;
+    let (mut center, span): (Coord<f64>, f64) = sampling::draw(&options.sampling, region, &mut rng);
+    let grid_size: f64 = common::target_grid_size(options);
+    let mut delta: f64 = common::initial_delta(&resols, span);
+    let mut score: f64 = evaluate(&resols, &center, delta);
+    let mut temperature: f64 = INITIAL_TEMPERATURE;
+    eprintln!("looking for a center by annealing, hang on...");
+    if 0. == score {
+        return Err(Error::NotFound(String::from(
+            "no structure is found inside the domain, try another random seed to change the initial condition",
+        )));
+    }
+    let mut naccepted: usize = 0;
+    for nproposals in 0..MAX_PROPOSALS {
+        if delta < grid_size {
+            eprintln!(
+                "{} out of {} proposals are accepted during the search",
+                naccepted, nproposals
+            );
+            common::canonicalize(region, &mut center);
+            eprintln!("it is found at ({:+.15e}, {:+.15e})", center.x, center.y);
+            return Ok((center, grid_size));
+        }
+        // propose a new state
+        let candidate: Coord<f64> = Coord {
+            x: center.x + rng.gen_range(-MAX_MOVE, MAX_MOVE) * resols.x as f64 * delta,
+            y: center.y + rng.gen_range(-MAX_MOVE, MAX_MOVE) * resols.y as f64 * delta,
+        };
+        let candidate_delta: f64 = delta * rng.gen_range(ZOOM_RANGE[0], ZOOM_RANGE[1]);
+        let threshold: f64 = rng.gen_range(0., 1.);
+        temperature = f64::max(MIN_TEMPERATURE, COOLING * temperature);
+        // drifting outside the region is forbidden if requested
+        if options.confine && !common::is_inside(region, &candidate) {
+            continue;
+        }
+        // proposals are judged on the rough image as well
+        let candidate_score: f64 = evaluate(&resols, &candidate, candidate_delta);
+        if 0. == candidate_score {
+            continue;
+        }
+        // Metropolis criterion
+        let change: f64 = candidate_score / score - 1.;
+        if 0. <= change || threshold < (change / temperature).exp() {
+            center = candidate;
+            delta = candidate_delta;
+            score = candidate_score;
+            naccepted += 1;
+            if DEBUG_ON {
+                eprintln!(
+                    "    delta: {:.1e} center: ({:+.1e}, {:+.1e}), score: {:.3e}",
+                    delta, center.x, center.y, score
+                );
+            }
+        }
+    }
+    // deep enough to be accepted
+    if let Some(min_zoom) = options.min_zoom {
+        if delta <= common::to_grid_size(&options.resols, min_zoom) {
+            eprintln!("no further structure is found, stop zooming");
+            common::canonicalize(region, &mut center);
+            eprintln!("it is found at ({:+.15e}, {:+.15e})", center.x, center.y);
+            return Ok((center, delta));
+        }
+    }
+    return Err(Error::NotFound(String::from(
+        "the annealing did not converge, try another random seed to change the initial condition",
+    )));
+}
+
+/// Computes the complexity of the given window normalised by the number of pixels.
+///
+/// * `resols` - The number of pixels of the rough image, given by [`common::search_resols`].
+/// * `center` - The center of the domain.
+/// * `delta`  - The inter-pixel size.
+fn evaluate(resols: &Coord<usize>, center: &Coord<f64>, delta: f64) -> f64 {
+    let points: Vec<Point> = solve::solve(resols, center, delta, 0.);
+    let complexity: u64 = common::complexities(resols, &points).iter().sum();
+    return complexity as f64 / (resols.x * resols.y) as f64;
+}
+
+#[cfg(test)]
+mod test_anneal {
+    use crate::coord::Coord;
+    use crate::mandelbrot::anneal::find_center;
+    use crate::mandelbrot::common;
+    use crate::options::{Options, Search};
+    /// Gives small images which are cheap to search.
+    fn options(seed: u64) -> Options {
+        return Options {
+            seed,
+            search: Search::Anneal,
+            resols: Coord { x: 128, y: 80 },
+            ..Default::default()
+        };
+    }
+    #[test]
+    fn test_determinism() -> () {
+        for seed in 0..2 {
+            let (center0, grid_size0): (Coord<f64>, f64) = find_center(&options(seed)).unwrap();
+            let (center1, grid_size1): (Coord<f64>, f64) = find_center(&options(seed)).unwrap();
+            assert_eq!(center0.x, center1.x);
+            assert_eq!(center0.y, center1.y);
+            assert_eq!(grid_size0, grid_size1);
+        }
+    }
+    #[test]
+    fn test_zoom_bounds() -> () {
+        for seed in 0..4 {
+            let mut options: Options = options(seed);
+            // deeper than the maximum zoom
+            options.grid_size = 1e-12;
+            options.min_zoom = Some(1e1);
+            options.max_zoom = Some(1e3);
+            let lower: f64 = common::to_grid_size(&options.resols, 1e3);
+            let upper: f64 = common::to_grid_size(&options.resols, 1e1);
+            let (_, grid_size): (Coord<f64>, f64) = find_center(&options).unwrap();
+            assert!(lower <= grid_size && grid_size <= upper);
+        }
+    }
+}
@@ -2,6 +2,11 @@
 
 //! Contains auxiliary routines used by [`crate::mandelbrot`] internally.  
 
+use crate::coord::Coord;
+use crate::mandelbrot::solve::rotate;
+use crate::mandelbrot::{Point, BASE_SPAN};
+use crate::options::Options;
+
 /// Computes the negtive / positive boundary of the given domain.
 ///   
 /// This is a private function intended to be used only inside [`crate::mandelbrot`].
@@ -14,3 +19,267 @@
 pub fn get_bound(sign: f64, resol: usize, center: f64, delta: f64, factor: f64) -> f64 {
     return center + sign * 0.5 * factor * resol as f64 * delta;
 }
+
+/// Splits a view into the views of several screens laid out on a desk,
+/// so that the screens show one consistent view across the gaps between them and their pixel densities.
+///   
+/// The bounding box of the screens is fitted to the view so that nothing is cropped,
+/// and the inter-pixel distance of each screen is proportional to its pixel pitch.
+/// Returns the center and the inter-pixel distance of each screen.
+///   
+/// * `resols`  - Number of pixels of the view in the two directions.
+/// * `center`  - Center of the view.
+/// * `delta`   - Inter-pixel distance of the view.
+/// * `angle`   - Rotation angle of the view around its center in radians.
+/// * `screens` - Number of pixels and physical lower and upper corners of each screen.
+pub fn split_view(
+    resols: &Coord<usize>,
+    center: &Coord<f64>,
+    delta: f64,
+    angle: f64,
+    screens: &[(Coord<usize>, [Coord<f64>; 2])],
+) -> Vec<(Coord<f64>, f64)> {
+    // bounding box of the screens in physical units
+    let mut lower: Coord<f64> = Coord {
+        x: f64::INFINITY,
+        y: f64::INFINITY,
+    };
+    let mut upper: Coord<f64> = Coord {
+        x: f64::NEG_INFINITY,
+        y: f64::NEG_INFINITY,
+    };
+    for (_, corners) in screens.iter() {
+        lower.x = f64::min(lower.x, corners[0].x);
+        lower.y = f64::min(lower.y, corners[0].y);
+        upper.x = f64::max(upper.x, corners[1].x);
+        upper.y = f64::max(upper.y, corners[1].y);
+    }
+    // distance in the complex plane per physical unit
+    let scale: f64 = f64::max(
+        resols.x as f64 * delta / (upper.x - lower.x),
+        resols.y as f64 * delta / (upper.y - lower.y),
+    );
+    // the view is extended to cover the bounding box
+    let corner: Coord<f64> = Coord {
+        x: get_bound(
+            -1.,
+            resols.x,
+            center.x,
+            delta,
+            (upper.x - lower.x) * scale / (resols.x as f64 * delta),
+        ),
+        y: get_bound(
+            -1.,
+            resols.y,
+            center.y,
+            delta,
+            (upper.y - lower.y) * scale / (resols.y as f64 * delta),
+        ),
+    };
+    let mut views: Vec<(Coord<f64>, f64)> = Vec::with_capacity(screens.len());
+    for (screen, corners) in screens.iter() {
+        let screen_delta: f64 = scale * (corners[1].x - corners[0].x) / screen.x as f64;
+        // inverse of get_bound, whose negative boundary is the corner of the screen
+        let screen_center: Coord<f64> = Coord {
+            x: corner.x + (corners[0].x - lower.x) * scale + 0.5 * screen.x as f64 * screen_delta,
+            y: corner.y + (corners[0].y - lower.y) * scale + 0.5 * screen.y as f64 * screen_delta,
+        };
+        // the screens rotate together around the center of the view
+        views.push((rotate(&screen_center, center, angle), screen_delta));
+    }
+    return views;
+}
+
+/// Computes the resolution of rough images used to find a center,
+/// which is a fraction of the final resolution.
+///   
+/// * `resols` - Number of pixels of the final image in the two directions.
+pub fn search_resols(resols: &Coord<usize>) -> Coord<usize> {
+    const SHRINKAGE: usize = 4;
+    return Coord {
+        x: resols.x / SHRINKAGE,
+        y: resols.y / SHRINKAGE,
+    };
+}
+
+/// Converts a zoom to the grid size of the final image.
+///   
+/// * `resols` - Number of pixels of the final image in the two directions.
+/// * `zoom`   - Zoom of interest.
+pub fn to_grid_size(resols: &Coord<usize>, zoom: f64) -> f64 {
+    return BASE_SPAN / (zoom * resols.x as f64);
+}
+
+/// Computes the grid size which the search aims at,
+/// i.e., `options.grid_size` clamped by `options.min_zoom` and `options.max_zoom` if given.
+///   
+/// * `options` - Fixed parameters which control the overall behaviour.
+pub fn target_grid_size(options: &Options) -> f64 {
+    let grid_size: f64 = match options.max_zoom {
+        Some(max_zoom) => f64::max(options.grid_size, to_grid_size(&options.resols, max_zoom)),
+        None => options.grid_size,
+    };
+    let grid_size: f64 = match options.min_zoom {
+        Some(min_zoom) => f64::min(grid_size, to_grid_size(&options.resols, min_zoom)),
+        None => grid_size,
+    };
+    return grid_size;
+}
+
+/// Computes the inter-pixel distance with which the given range is covered by the image.
+///   
+/// * `resols` - Number of pixels in the two directions.
+/// * `span`   - Range of the complex plane to be covered.
+pub fn initial_delta(resols: &Coord<usize>, span: f64) -> f64 {
+    let deltas: Coord<f64> = Coord {
+        x: span / resols.x as f64,
+        y: span / resols.y as f64,
+    };
+    return if deltas.y < deltas.x {
+        deltas.x
+    } else {
+        deltas.y
+    };
+}
+
+/// Checks whether the given point is inside the rectangle.
+///   
+/// * `region` - Lower-left and upper-right corners of the rectangle.
+/// * `point`  - Point of interest.
+pub fn is_inside(region: &[Coord<f64>; 2], point: &Coord<f64>) -> bool {
+    return region[0].x <= point.x
+        && point.x <= region[1].x
+        && region[0].y <= point.y
+        && point.y <= region[1].y;
+}
+
+/// Mirrors a center in the lower half to the upper half if it stays inside the region.
+///   
+/// Locations mirroring each other across the real axis give the same image,
+/// which are thus unified.
+///   
+/// * `region` - Lower-left and upper-right corners of the rectangle.
+/// * `center` - Center of the image, which is intent-in/out.
+pub fn canonicalize(region: &[Coord<f64>; 2], center: &mut Coord<f64>) -> () {
+    let mirrored: Coord<f64> = Coord {
+        x: center.x,
+        y: -center.y,
+    };
+    if center.y < 0. && is_inside(region, &mirrored) {
+        *center = mirrored;
+    }
+}
+
+/// Computes the complexity, i.e., the number of boundaries between diverging and non-diverging pixels,
+/// of the four sub-domains (lower-left, lower-right, upper-left, and upper-right).
+///   
+/// * `resols` - The number of pixels in two directions.
+/// * `points` - Result of the reccurence relation for each pixel.
+pub fn complexities(resols: &Coord<usize>, points: &[Point]) -> [u64; 4] {
+    let mut complexities: [u64; 4] = [0; 4];
+    for n in 0..resols.x * resols.y {
+        let i: usize = n % resols.x;
+        let j: usize = n / resols.x;
+        // four sub-domains
+        let ci: usize = if i < resols.x / 2 { 0 } else { 1 };
+        let cj: usize = if j < resols.y / 2 { 0 } else { 1 };
+        // skip edge
+        if 0 == i || resols.x - 1 == i {
+            continue;
+        }
+        if 0 == j || resols.y - 1 == j {
+            continue;
+        }
+        // interested in four neighbouring points
+        let indices: [usize; 5] = [
+            j * resols.x + i,
+            j * resols.x + (i - 1),
+            j * resols.x + (i + 1),
+            (j - 1) * resols.x + i,
+            (j + 1) * resols.x + i,
+        ];
+        // compute XOR to check the boundary exists
+        let mut are_diverged: [u64; 5] = [0; 5];
+        for m in 0..5 {
+            are_diverged[m] = if points[indices[m]].is_diverged { 1 } else { 0 };
+        }
+        let mut complexity: u64 = 0;
+        for m in 1..5 {
+            complexity += are_diverged[m] ^ are_diverged[0];
+        }
+        complexities[cj * 2 + ci] += complexity;
+    }
+    return complexities;
+}
+
+#[cfg(test)]
+mod test_is_inside {
+    use crate::mandelbrot::common::is_inside;
+    use crate::mandelbrot::Coord;
+    #[test]
+    fn test0() -> () {
+        let region: [Coord<f64>; 2] = [Coord { x: -1., y: 0. }, Coord { x: 1., y: 2. }];
+        assert!(is_inside(&region, &Coord { x: 0., y: 1. }));
+        assert!(is_inside(&region, &Coord { x: 1., y: 0. }));
+        assert!(!is_inside(&region, &Coord { x: 0., y: -1. }));
+        assert!(!is_inside(&region, &Coord { x: 2., y: 1. }));
+    }
+}
+
+#[cfg(test)]
+mod test_complexities {
+    use crate::mandelbrot::common::complexities;
+    use crate::mandelbrot::Coord;
+    use crate::mandelbrot::Point;
+    #[test]
+    fn test0() -> () {
+        // single non-diverging pixel in the lower-left sub-domain
+        let resols: Coord<usize> = Coord { x: 6, y: 6 };
+        let mut points: Vec<Point> = vec![
+            Point {
+                is_diverged: true,
+                iter: 1,
+                smooth: 1.,
+                distance: 1.,
+            };
+            36
+        ];
+        points[6 + 1].is_diverged = false;
+        assert_eq!([6, 0, 0, 0], complexities(&resols, &points));
+    }
+}
+
+#[cfg(test)]
+mod test_split_view {
+    use crate::mandelbrot::common::{get_bound, split_view};
+    use crate::mandelbrot::Coord;
+    #[test]
+    fn test0() -> () {
+        // two screens side by side with a gap of 10, the right one having twice the pixel density
+        let screens: [(Coord<usize>, [Coord<f64>; 2]); 2] = [
+            (
+                Coord { x: 100, y: 50 },
+                [Coord { x: 0., y: 0. }, Coord { x: 100., y: 50. }],
+            ),
+            (
+                Coord { x: 200, y: 100 },
+                [Coord { x: 110., y: 0. }, Coord { x: 210., y: 50. }],
+            ),
+        ];
+        let resols: Coord<usize> = Coord { x: 210, y: 50 };
+        let center: Coord<f64> = Coord { x: 0., y: 0. };
+        let views: Vec<(Coord<f64>, f64)> = split_view(&resols, &center, 1., 0., &screens);
+        assert_eq!(1., views[0].1);
+        assert_eq!(0.5, views[1].1);
+        // the boundaries keep the physical layout
+        let bound = |n: usize, sign: f64| -> f64 {
+            return get_bound(sign, screens[n].0.x, views[n].0.x, views[n].1, 1.);
+        };
+        assert_eq!(-105., bound(0, -1.));
+        assert_eq!(-5., bound(0, 1.));
+        assert_eq!(5., bound(1, -1.));
+        assert_eq!(105., bound(1, 1.));
+        assert_eq!(0., views[0].0.y);
+        assert_eq!(0., views[1].0.y);
+    }
+}
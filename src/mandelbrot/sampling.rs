@@ -0,0 +1,152 @@
+#![deny(missing_docs)]
+
+//! Draws the initial center of the search.
+//!
+//! Starting points deep inside the main cardioid or far outside the set tend to make the search fail,
+//! since no structure remains inside the domain after a few zoom-ins.
+//! To avoid this, candidates are tested by a cheap escape test and rejected unless the set boundary is nearby.
+
+use crate::coord::Coord;
+use crate::mandelbrot::{solve, BASE_SPAN};
+use crate::options::Sampling;
+use crate::random::Random;
+
+/// Maximum number of iterations of the coarse escape test.
+const COARSE_MAX_ITER: u64 = 256;
+
+/// Ratios of the stencil radii of the coarse escape test to the size of the region,
+/// so that the boundary is checked to persist over several scales.
+const COARSE_RADII: [f64; 3] = [1. / 32., 1. / 256., 1. / 2048.];
+
+/// Number of points on the stencil of the coarse escape test.
+const COARSE_NPOINTS: usize = 8;
+
+/// Ratio of the initial image range of the search to the size of the region,
+/// used when the center is drawn by the rejection.
+const SPAN_RATIO: f64 = 1. / 32.;
+
+/// Maximum number of candidates, after which the last candidate is accepted.
+const MAX_TRIALS: u64 = 4096;
+
+/// Draws the initial center of the search inside the given region.
+///
+/// Returns the center and the initial image range of the search.
+/// Since the boundary is confirmed to persist around the center drawn by the rejection,
+/// the search starts from a narrower range than the whole set.
+///
+/// * `sampling` - Strategy to draw the center.
+/// * `region`   - Lower-left and upper-right corners of the region.
+/// * `rng`      - Random number generator.
+pub fn draw(sampling: &Sampling, region: &[Coord<f64>; 2], rng: &mut Random) -> (Coord<f64>, f64) {
+    let size: f64 = f64::min(region[1].x - region[0].x, region[1].y - region[0].y);
+    let radii: [f64; 3] = COARSE_RADII.map(|ratio| ratio * size);
+    let span: f64 = f64::min(BASE_SPAN, SPAN_RATIO * size);
+    let mut candidate: Coord<f64> = Coord { x: 0., y: 0. };
+    match sampling {
+        Sampling::Uniform => {
+            let center: Coord<f64> = Coord {
+                x: rng.gen_range(region[0].x, region[1].x),
+                y: rng.gen_range(region[0].y, region[1].y),
+            };
+            return (center, BASE_SPAN);
+        }
+        Sampling::Boundary => {
+            for _ in 0..MAX_TRIALS {
+                candidate = Coord {
+                    x: rng.gen_range(region[0].x, region[1].x),
+                    y: rng.gen_range(region[0].y, region[1].y),
+                };
+                if radii
+                    .iter()
+                    .all(|&radius| is_near_boundary(&candidate, radius))
+                {
+                    return (candidate, span);
+                }
+            }
+        }
+        Sampling::Halton => {
+            // the random number decides where the low-discrepancy sequence starts
+            let start: u64 = rng.gen_integer(1, 1u64 << 32);
+            for index in start..start + MAX_TRIALS {
+                candidate = Coord {
+                    x: region[0].x + (region[1].x - region[0].x) * halton(index, 2),
+                    y: region[0].y + (region[1].y - region[0].y) * halton(index, 3),
+                };
+                if radii
+                    .iter()
+                    .all(|&radius| is_near_boundary(&candidate, radius))
+                {
+                    return (candidate, span);
+                }
+            }
+        }
+    };
+    eprintln!("no boundary is found by sampling, use the last candidate");
+    return (candidate, BASE_SPAN);
+}
+
+/// Checks whether both diverging and non-diverging points exist around the given point.
+///
+/// * `center` - Point of interest.
+/// * `radius` - Distance between the point and its neighbours to be checked.
+fn is_near_boundary(center: &Coord<f64>, radius: f64) -> bool {
+    let is_diverged: bool = solve::iterate(center, COARSE_MAX_ITER).is_diverged;
+    for n in 0..COARSE_NPOINTS {
+        let theta: f64 = 2. * std::f64::consts::PI * n as f64 / COARSE_NPOINTS as f64;
+        let point: Coord<f64> = Coord {
+            x: center.x + radius * theta.cos(),
+            y: center.y + radius * theta.sin(),
+        };
+        if is_diverged != solve::iterate(&point, COARSE_MAX_ITER).is_diverged {
+            return true;
+        }
+    }
+    return false;
+}
+
+/// Computes the `index`-th element of the Halton sequence, which is in [0:1).
+///
+/// * `index` - Index of the element (positive).
+/// * `base`  - Base of the sequence, which should be a prime number.
+fn halton(index: u64, base: u64) -> f64 {
+    let mut index: u64 = index;
+    let mut factor: f64 = 1.;
+    let mut value: f64 = 0.;
+    while 0 < index {
+        factor /= base as f64;
+        value += factor * (index % base) as f64;
+        index /= base;
+    }
+    return value;
+}
+
+#[cfg(test)]
+mod test_halton {
+    use crate::mandelbrot::sampling::halton;
+    #[test]
+    fn test0() -> () {
+        assert_eq!(0.5, halton(1, 2));
+        assert_eq!(0.25, halton(2, 2));
+        assert_eq!(0.75, halton(3, 2));
+    }
+    #[test]
+    fn test1() -> () {
+        assert!((halton(1, 3) - 1. / 3.).abs() < 1e-15);
+        assert!((halton(5, 3) - 7. / 9.).abs() < 1e-15);
+    }
+}
+
+#[cfg(test)]
+mod test_is_near_boundary {
+    use crate::mandelbrot::sampling::is_near_boundary;
+    use crate::mandelbrot::Coord;
+    #[test]
+    fn test0() -> () {
+        // deep inside the main cardioid
+        assert!(!is_near_boundary(&Coord { x: -0.1, y: 0. }, 0.01));
+        // far outside the set
+        assert!(!is_near_boundary(&Coord { x: 1.5, y: 1.5 }, 0.01));
+        // across the cusp of the main cardioid
+        assert!(is_near_boundary(&Coord { x: 0.25, y: 0. }, 0.05));
+    }
+}
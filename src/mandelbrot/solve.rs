@@ -6,7 +6,11 @@ use crate::coord::Coord;
 use crate::mandelbrot::{common, Point};
 
 /// maximum number of iterations, above which the recurrence relation is considered to converge.
-const MAX_ITER: u64 = 1024u64;
+pub const MAX_ITER: u64 = 1024u64;
+
+/// number of extra iterations after the divergence,
+/// with which the smooth iteration count and the distance estimate become accurate.
+const EXTRA_ITER: u64 = 4u64;
 
 /// Solves the recurrence relation for each pixel to see the convergence
 /// and to count the number of iterations needed to diverge.
@@ -14,7 +18,8 @@ const MAX_ITER: u64 = 1024u64;
 /// * `resols` - The number of pixels in two directions.
 /// * `center` - The center of the domain.
 /// * `delta`  - The inter-pixel size.
-pub fn solve(resols: &Coord<usize>, center: &Coord<f64>, delta: f64) -> Vec<Point> {
+/// * `angle`  - Rotation angle of the domain around its center in radians.
+pub fn solve(resols: &Coord<usize>, center: &Coord<f64>, delta: f64, angle: f64) -> Vec<Point> {
     // prepare buffers to store the results
     let nitems: usize = resols.x * resols.y;
     let mut points: Vec<Point> = Vec::with_capacity(nitems);
@@ -22,6 +27,8 @@ pub fn solve(resols: &Coord<usize>, center: &Coord<f64>, delta: f64) -> Vec<Poin
         points.push(Point {
             is_diverged: false,
             iter: 0u64,
+            smooth: 0f64,
+            distance: 0f64,
         });
     }
     // check left-bottom domain corner
@@ -30,31 +37,147 @@ pub fn solve(resols: &Coord<usize>, center: &Coord<f64>, delta: f64) -> Vec<Poin
         y: common::get_bound(-1., resols.y, center.y, delta, 1.),
     };
     // for each pixel, solve recurrence relation
-    for n in 0..nitems {
-        let point: Coord<f64> = {
+    for (n, point) in points.iter_mut().enumerate() {
+        let p0: Coord<f64> = {
             let i: usize = n % resols.x;
             let j: usize = n / resols.x;
             let x: f64 = corner.x + i as f64 * delta;
             let y: f64 = corner.y + j as f64 * delta;
             Coord { x, y }
         };
-        points[n] = kernel(&point);
+        let p0: Coord<f64> = if 0. == angle {
+            p0
+        } else {
+            rotate(&p0, center, angle)
+        };
+        *point = kernel(&p0);
     }
     return points;
 }
 
+/// Stores the results of the recurrence relation on a uniform grid, so that they can be reused.
+pub struct Samples {
+    /// The number of pixels in two directions.
+    pub resols: Coord<usize>,
+    /// The center of the domain.
+    pub center: Coord<f64>,
+    /// The inter-pixel size.
+    pub delta: f64,
+    /// Results of the recurrence relation for each pixel.
+    pub points: Vec<Point>,
+}
+
+/// Solves the recurrence relation for each pixel while reusing the results on another grid.
+///   
+/// A sample of the previous grid is copied only when its position coincides exactly with the pixel,
+/// and every other pixel is computed, so that the results are identical to those of [`solve`].
+/// Returns the results and the number of pixels actually computed.
+///   
+/// * `resols`   - The number of pixels in two directions.
+/// * `center`   - The center of the domain.
+/// * `delta`    - The inter-pixel size.
+/// * `previous` - Results on another grid.
+pub fn solve_reusing(
+    resols: &Coord<usize>,
+    center: &Coord<f64>,
+    delta: f64,
+    previous: &Samples,
+) -> (Vec<Point>, usize) {
+    let nitems: usize = resols.x * resols.y;
+    let mut points: Vec<Point> = Vec::with_capacity(nitems);
+    let mut ncomputed: usize = 0;
+    // check left-bottom domain corners
+    let corner: Coord<f64> = Coord {
+        x: common::get_bound(-1., resols.x, center.x, delta, 1.),
+        y: common::get_bound(-1., resols.y, center.y, delta, 1.),
+    };
+    let previous_corner: Coord<f64> = Coord {
+        x: common::get_bound(
+            -1.,
+            previous.resols.x,
+            previous.center.x,
+            previous.delta,
+            1.,
+        ),
+        y: common::get_bound(
+            -1.,
+            previous.resols.y,
+            previous.center.y,
+            previous.delta,
+            1.,
+        ),
+    };
+    // index of the sample of the previous grid lying exactly at the given position, if any
+    let coincide = |value: f64, lower: f64, resol: usize| -> Option<usize> {
+        let index: f64 = ((value - lower) / previous.delta).round();
+        if index < 0. || resol as f64 <= index {
+            return None;
+        }
+        // compared with the position computed in the same way as solve
+        return if lower + index * previous.delta == value {
+            Some(index as usize)
+        } else {
+            None
+        };
+    };
+    for n in 0..nitems {
+        let i: usize = n % resols.x;
+        let j: usize = n / resols.x;
+        let point: Coord<f64> = Coord {
+            x: corner.x + i as f64 * delta,
+            y: corner.y + j as f64 * delta,
+        };
+        let i0: Option<usize> = coincide(point.x, previous_corner.x, previous.resols.x);
+        let j0: Option<usize> = coincide(point.y, previous_corner.y, previous.resols.y);
+        if let (Some(i0), Some(j0)) = (i0, j0) {
+            points.push(previous.points[j0 * previous.resols.x + i0]);
+            continue;
+        }
+        points.push(kernel(&point));
+        ncomputed += 1;
+    }
+    return (points, ncomputed);
+}
+
+/// Rotates a point around the given pivot.
+///   
+/// * `point` - A point to be rotated.
+/// * `pivot` - Center of the rotation.
+/// * `angle` - Rotation angle in radians (counter-clockwise).
+pub fn rotate(point: &Coord<f64>, pivot: &Coord<f64>, angle: f64) -> Coord<f64> {
+    let (sin, cos): (f64, f64) = angle.sin_cos();
+    let dx: f64 = point.x - pivot.x;
+    let dy: f64 = point.y - pivot.y;
+    return Coord {
+        x: pivot.x + cos * dx - sin * dy,
+        y: pivot.y + sin * dx + cos * dy,
+    };
+}
+
 /// Solves the recurrence relation for a single given point.
 ///   
 /// * `p0` - A specific point in the complex plane to which the recurrence relation is considered.
 fn kernel(p0: &Coord<f64>) -> Point {
+    return iterate(p0, MAX_ITER);
+}
+
+/// Solves the recurrence relation for a single given point up to the given number of iterations.
+///   
+/// The derivative with respect to `c` is also integrated, giving the distance estimate of a diverging point.
+///   
+/// * `p0`       - A specific point in the complex plane to which the recurrence relation is considered.
+/// * `max_iter` - Maximum number of iterations, above which the point is considered to converge.
+pub fn iterate(p0: &Coord<f64>, max_iter: u64) -> Point {
     // p0: given   complex number (c)
     // p1: current complex number (z^n)
     // p2: next    complex number (z^{n+1})
+    // d1: derivative of z^n with respect to c
     let mut p1: Coord<f64> = {
         let x: f64 = 0.;
         let y: f64 = 0.;
         Coord { x, y }
     };
+    let mut d1: Coord<f64> = Coord { x: 0., y: 0. };
     // solve recurrence relation to determine
     //   this pixel is inside/outside the Mandelbrot set
     let mut iter: u64 = 0;
@@ -65,27 +188,65 @@ fn kernel(p0: &Coord<f64>) -> Point {
             let y: f64 = p0.y + 2. * p1.x * p1.y;
             Coord { x, y }
         };
-        iter = iter + 1;
+        // compute dz^{n+1} = 2 z^n dz^n + 1
+        let d2: Coord<f64> = Coord {
+            x: 2. * (p1.x * d1.x - p1.y * d1.y) + 1.,
+            y: 2. * (p1.x * d1.y + p1.y * d1.x),
+        };
+        iter += 1;
         // check whether maximum number of iteration has been reached,
         //   i.e., this point has not diverged and thus inside Mandelbrot set
-        if MAX_ITER < iter {
+        if max_iter < iter {
             return Point {
                 is_diverged: false,
-                iter: MAX_ITER,
+                iter: max_iter,
+                smooth: max_iter as f64,
+                distance: 0.,
             };
         }
         // check L^2 on the complex plane to see the divergence
         if 4. < p2.x.powi(2i32) + p2.y.powi(2i32) {
+            let (smooth, distance): (f64, f64) = escape(p0, &p2, &d2, iter);
             return Point {
                 is_diverged: true,
-                iter: iter,
+                iter,
+                smooth,
+                distance,
             };
         }
         // update z^n
         p1 = p2;
+        d1 = d2;
     }
 }
 
+/// Continues the recurrence relation of a diverged point for a few iterations,
+/// and gives the continuous number of iterations and the distance estimate.
+///   
+/// * `p0`   - A specific point in the complex plane to which the recurrence relation is considered.
+/// * `p1`   - The first z^n exceeding the escape radius.
+/// * `d1`   - Derivative of z^n with respect to c.
+/// * `iter` - Number of iterations to diverge.
+fn escape(p0: &Coord<f64>, p1: &Coord<f64>, d1: &Coord<f64>, iter: u64) -> (f64, f64) {
+    let mut p1: Coord<f64> = *p1;
+    let mut d1: Coord<f64> = *d1;
+    for _ in 0..EXTRA_ITER {
+        d1 = Coord {
+            x: 2. * (p1.x * d1.x - p1.y * d1.y) + 1.,
+            y: 2. * (p1.x * d1.y + p1.y * d1.x),
+        };
+        p1 = Coord {
+            x: p0.x + p1.x * p1.x - p1.y * p1.y,
+            y: p0.y + 2. * p1.x * p1.y,
+        };
+    }
+    let norm: f64 = f64::hypot(p1.x, p1.y);
+    // the extra iterations are cancelled by the double logarithm
+    let smooth: f64 = (iter + EXTRA_ITER) as f64 + 1. - norm.ln().ln() / std::f64::consts::LN_2;
+    let distance: f64 = norm * norm.ln() / f64::hypot(d1.x, d1.y);
+    return (smooth, distance);
+}
+
 #[cfg(test)]
 mod test_kernel {
     use crate::mandelbrot::solve::kernel;
@@ -111,3 +272,82 @@ mod test_kernel {
         assert_eq!(MAX_ITER, point.iter);
     }
 }
+
+#[cfg(test)]
+mod test_rotate {
+    use crate::mandelbrot::solve::rotate;
+    use crate::mandelbrot::Coord;
+    #[test]
+    fn test0() -> () {
+        let point: Coord<f64> = rotate(
+            &Coord::<f64> { x: 2., y: 1. },
+            &Coord::<f64> { x: 1., y: 1. },
+            0.5 * std::f64::consts::PI,
+        );
+        assert!((point.x - 1.).abs() < 1e-15);
+        assert!((point.y - 2.).abs() < 1e-15);
+    }
+}
+
+#[cfg(test)]
+mod test_solve_reusing {
+    use crate::mandelbrot::solve::{solve, solve_reusing, Samples};
+    use crate::mandelbrot::Coord;
+    use crate::mandelbrot::Point;
+    /// Solves the grid of the given center and inter-pixel size.
+    fn samples(resols: Coord<usize>, center: Coord<f64>, delta: f64) -> Samples {
+        return Samples {
+            resols,
+            center,
+            delta,
+            points: solve(&resols, &center, delta, 0.),
+        };
+    }
+    /// Checks the results of the two grids are identical.
+    fn assert_identical(expected: &[Point], points: &[Point]) -> () {
+        for (p0, p1) in expected.iter().zip(points.iter()) {
+            assert_eq!(p0.is_diverged, p1.is_diverged);
+            assert_eq!(p0.iter, p1.iter);
+            assert_eq!(p0.smooth.to_bits(), p1.smooth.to_bits());
+        }
+    }
+    #[test]
+    fn test0() -> () {
+        // reusing the identical grid computes nothing
+        let resols: Coord<usize> = Coord { x: 64, y: 48 };
+        let previous: Samples = samples(resols, Coord { x: -0.75, y: 0. }, 4. / 64.);
+        let (points, ncomputed): (Vec<Point>, usize) =
+            solve_reusing(&resols, &previous.center, previous.delta, &previous);
+        assert_eq!(0, ncomputed);
+        assert_identical(&previous.points, &points);
+    }
+    #[test]
+    fn test1() -> () {
+        // zoomed twice sharing the lower-left corner: every other pixel in each direction is reused
+        let resols: Coord<usize> = Coord { x: 64, y: 48 };
+        let previous: Samples = samples(resols, Coord { x: 0., y: 0. }, 1. / 16.);
+        let expected: Samples = samples(resols, Coord { x: -1., y: -0.75 }, 1. / 32.);
+        let (points, ncomputed): (Vec<Point>, usize) =
+            solve_reusing(&resols, &expected.center, expected.delta, &previous);
+        assert_eq!(3 * resols.x * resols.y / 4, ncomputed);
+        assert_identical(&expected.points, &points);
+    }
+    #[test]
+    fn test2() -> () {
+        // shifted by half a pixel: nothing coincides and everything is computed
+        let resols: Coord<usize> = Coord { x: 64, y: 48 };
+        let previous: Samples = samples(resols, Coord { x: 0., y: 0. }, 1. / 16.);
+        let expected: Samples = samples(
+            resols,
+            Coord {
+                x: 1. / 32.,
+                y: 1. / 32.,
+            },
+            1. / 16.,
+        );
+        let (points, ncomputed): (Vec<Point>, usize) =
+            solve_reusing(&resols, &expected.center, expected.delta, &previous);
+        assert_eq!(resols.x * resols.y, ncomputed);
+        assert_identical(&expected.points, &points);
+    }
+}
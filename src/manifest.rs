@@ -0,0 +1,297 @@
+#![deny(missing_docs)]
+
+//! Records everything needed to reproduce an image in a sidecar file.
+//!
+//! The manifest is a plain-text file consisting of `key=value` lines,
+//! whose keys coincide with those of the command-line arguments (see [`crate::options`])
+//! so that it can be fed back to reproduce the image via `--from_manifest=<file>`.
+//! The other records (version, iteration limit, timings, etc.) are informative,
+//! and are skipped when the manifest is fed back (see [`is_informative()`]).
+
+use crate::atomic;
+use crate::coord::Coord;
+use crate::error::Error;
+use crate::mandelbrot;
+use crate::options::Options;
+
+/// Tells whether a record is informative, which is not an option and thus not fed back.
+///
+/// * `key` - Name of the record.
+pub fn is_informative(key: &str) -> bool {
+    const KEYS: [&str; 3] = ["version", "max_iter", "fractal"];
+    const PREFIXES: [&str; 2] = ["time_", "quality_"];
+    return KEYS.contains(&key) || PREFIXES.iter().any(|prefix| key.starts_with(prefix));
+}
+
+/// Stores a sequence of key-value records.
+pub struct Manifest {
+    /// key-value pairs in the order of insertion
+    pub records: Vec<(String, String)>,
+}
+
+impl Manifest {
+    /// Collects the records describing the given run.
+    ///
+    /// * `options` - Fixed parameters used to render the image.
+    /// * `center`  - Center of the image, which is either given or found.
+    /// * `timings` - Pairs of the name of each step and the time spent.
+    pub fn new(
+        options: &Options,
+        center: &Coord<f64>,
+        timings: &[(&str, std::time::Duration)],
+    ) -> Manifest {
+        let mut manifest: Manifest = Manifest {
+            records: Vec::new(),
+        };
+        // f64 values are written in the shortest representation which round-trips
+        manifest.push("version", env!("CARGO_PKG_VERSION"));
+        manifest.push("seed", options.seed);
+        manifest.push("grid_size", options.grid_size);
+        manifest.push("width", options.resols.x);
+        manifest.push("height", options.resols.y);
+        manifest.push("fname", &options.fname);
+        manifest.push("format", &options.format);
+        manifest.push("bit_depth", options.bit_depth);
+        manifest.push("alpha", options.alpha);
+        manifest.push("dither", &options.dither);
+        if let Some(location) = &options.location {
+            manifest.push("location", location);
+            manifest.push("location_as", &options.location_as);
+        }
+        if let Some(bookmarks) = &options.bookmarks {
+            manifest.push("bookmarks", bookmarks);
+        }
+        if let Some(history) = &options.history {
+            manifest.push("history", history);
+        }
+        manifest.push("keep", options.keep);
+        manifest.push("center_x", center.x);
+        manifest.push("center_y", center.y);
+        manifest.push("search", &options.search);
+        manifest.push(
+            "region",
+            format!(
+                "{},{},{},{}",
+                options.region[0].x, options.region[1].x, options.region[0].y, options.region[1].y
+            ),
+        );
+        manifest.push("sampling", &options.sampling);
+        manifest.push("confine", options.confine);
+        if let Some(min_zoom) = options.min_zoom {
+            manifest.push("min_zoom", min_zoom);
+        }
+        if let Some(max_zoom) = options.max_zoom {
+            manifest.push("max_zoom", max_zoom);
+        }
+        if let Some(data) = &options.data {
+            manifest.push("data", data);
+        }
+        manifest.push("max_interior", options.max_interior);
+        manifest.push("min_colour_variance", options.min_colour_variance);
+        manifest.push("min_edge_density", options.min_edge_density);
+        manifest.push("min_histogram_spread", options.min_histogram_spread);
+        manifest.push("retries", options.retries);
+        manifest.push("rotation", options.rotation);
+        manifest.push("palette", &options.palette);
+        manifest.push("palette_mode", &options.palette_mode);
+        manifest.push("palette_offset", options.palette_offset);
+        manifest.push("palette_repeat", options.palette_repeat);
+        manifest.push("colour_space", &options.colour_space);
+        if let Some(simulate) = &options.simulate {
+            manifest.push("simulate", simulate);
+        }
+        manifest.push("transfer", &options.transfer);
+        manifest.push("transfer_exponent", options.transfer_exponent);
+        manifest.push("transfer_steepness", options.transfer_steepness);
+        if let Some(clip) = options.clip {
+            manifest.push("clip", format!("{},{}", clip[0], clip[1]));
+        }
+        if let Some(relief) = &options.relief {
+            manifest.push("relief", relief);
+        }
+        manifest.push("light_azimuth", options.light_azimuth);
+        manifest.push("light_elevation", options.light_elevation);
+        manifest.push("ambient", options.ambient);
+        manifest.push("specular", options.specular);
+        manifest.push("relief_strength", options.relief_strength);
+        manifest.push("relief_blend", options.relief_blend);
+        if let Some(keyframes) = &options.keyframes {
+            manifest.push("keyframes", keyframes);
+        }
+        manifest.push("fps", options.fps);
+        manifest.push("threads", options.threads);
+        manifest.push("resume_from", options.resume_from);
+        if !options.sizes.is_empty() {
+            let sizes: Vec<String> = options
+                .sizes
+                .iter()
+                .map(|resols| format!("{}x{}", resols.x, resols.y))
+                .collect();
+            manifest.push("sizes", sizes.join(","));
+        }
+        manifest.push("size_scale", &options.size_scale);
+        if let Some(layout) = &options.layout {
+            manifest.push("layout", layout);
+        }
+        manifest.push("max_iter", mandelbrot::MAX_ITER);
+        manifest.push("fractal", "mandelbrot");
+        for (name, duration) in timings.iter() {
+            manifest.push(&format!("time_{}", name), duration.as_secs_f64());
+        }
+        return manifest;
+    }
+
+    /// Collects the records embedded in the image itself, with which the view can be rendered again or zoomed further.
+    ///
+    /// * `options` - Fixed parameters used to render the image.
+    /// * `center`  - Center of the image.
+    pub fn embedded(options: &Options, center: &Coord<f64>) -> Manifest {
+        let mut manifest: Manifest = Manifest {
+            records: Vec::new(),
+        };
+        manifest.push("version", env!("CARGO_PKG_VERSION"));
+        manifest.push("seed", options.seed);
+        manifest.push("center_x", center.x);
+        manifest.push("center_y", center.y);
+        manifest.push("grid_size", options.grid_size);
+        // the field of view is kept when the image is rendered in another size
+        manifest.push("width", options.resols.x);
+        manifest.push("height", options.resols.y);
+        manifest.push("rotation", options.rotation);
+        manifest.push("max_iter", mandelbrot::MAX_ITER);
+        manifest.push("palette", &options.palette);
+        return manifest;
+    }
+
+    /// Appends a record.
+    ///
+    /// * `key`   - Name of the record.
+    /// * `value` - Value of the record.
+    pub fn push<T: std::fmt::Display>(&mut self, key: &str, value: T) -> () {
+        self.records.push((String::from(key), value.to_string()));
+    }
+
+    /// Finds the value of the first record having the given key.
+    ///
+    /// * `key` - Name of the record.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        return self
+            .records
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str());
+    }
+
+    /// Writes the records to a file.
+    ///
+    /// * `fname` - Name of the manifest file.
+    pub fn write(&self, fname: &str) -> Result<(), Error> {
+        let mut contents: String = String::from("# reproduce with --from_manifest=<this file>\n");
+        for (key, value) in self.records.iter() {
+            contents.push_str(&format!("{}={}\n", key, value));
+        }
+        return atomic::write(fname, contents.as_bytes());
+    }
+
+    /// Reads the records from a file, or from the standard input when the file name is `-`.
+    ///
+    /// * `fname` - Name of the manifest file.
+    pub fn load(fname: &str) -> Result<Manifest, Error> {
+        let contents: std::io::Result<String> = if "-" == fname {
+            std::io::read_to_string(std::io::stdin())
+        } else {
+            std::fs::read_to_string(fname)
+        };
+        let contents: String = match contents {
+            Ok(contents) => contents,
+            Err(error) => return Err(Error::Read(String::from(fname), error)),
+        };
+        return match Manifest::parse(&contents) {
+            Ok(manifest) => Ok(manifest),
+            Err(msg) => Err(Error::Parse(String::from(fname), msg.into())),
+        };
+    }
+
+    /// Converts the contents of a manifest file to records.
+    ///
+    /// * `contents` - Whole text of the manifest file.
+    pub fn parse(contents: &str) -> Result<Manifest, String> {
+        let mut manifest: Manifest = Manifest {
+            records: Vec::new(),
+        };
+        for (n, line) in contents.lines().enumerate() {
+            let line: &str = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match line.split_once('=') {
+                Some((key, value)) => manifest.push(key.trim(), value.trim()),
+                None => return Err(format!("line {}: expect key=value", n + 1)),
+            };
+        }
+        return Ok(manifest);
+    }
+}
+
+/// Gives the name of the manifest file attached to an image, e.g., `image.ppm` to `image.manifest`.
+///
+/// * `fname` - Name of the image file.
+pub fn sidecar_fname(fname: &str) -> String {
+    let path: std::path::PathBuf = std::path::Path::new(fname).with_extension("manifest");
+    return path.to_string_lossy().into_owned();
+}
+
+#[cfg(test)]
+mod test_manifest {
+    use crate::coord::Coord;
+    use crate::manifest::{self, sidecar_fname, Manifest};
+    use crate::options::Options;
+    #[test]
+    fn test_new() -> () {
+        // the options affecting the output are recorded
+        let options: Options = Options {
+            history: Some(String::from("history.txt")),
+            sizes: vec![Coord { x: 640, y: 400 }, Coord { x: 320, y: 200 }],
+            layout: Some(String::from("layout.txt")),
+            ..Options::default()
+        };
+        let manifest: Manifest = Manifest::new(&options, &Coord { x: -0.5, y: 0.1 }, &[]);
+        assert_eq!(Some("history.txt"), manifest.get("history"));
+        assert_eq!(Some("640x400,320x200"), manifest.get("sizes"));
+        assert_eq!(Some("layout.txt"), manifest.get("layout"));
+        assert!(manifest.get("keep").is_some());
+        assert!(manifest.get("size_scale").is_some());
+        assert!(manifest::is_informative("version"));
+        assert!(manifest::is_informative("time_solve"));
+        assert!(!manifest::is_informative("seed"));
+    }
+    #[test]
+    fn test_parse0() -> () {
+        let manifest: Manifest =
+            Manifest::parse("# comment\nseed=3\n\ncenter_x = -0.75\n").unwrap();
+        assert_eq!(2, manifest.records.len());
+        assert_eq!(Some("3"), manifest.get("seed"));
+        assert_eq!(Some("-0.75"), manifest.get("center_x"));
+        assert_eq!(None, manifest.get("center_y"));
+    }
+    #[test]
+    fn test_parse1() -> () {
+        assert!(Manifest::parse("seed\n").is_err());
+    }
+    #[test]
+    fn test_round_trip() -> () {
+        let value: f64 = -0.743_643_887_037_158_7;
+        let mut manifest: Manifest = Manifest {
+            records: Vec::new(),
+        };
+        manifest.push("center_x", value);
+        let value_: f64 = manifest.get("center_x").unwrap().parse::<f64>().unwrap();
+        assert_eq!(value, value_);
+    }
+    #[test]
+    fn test_sidecar_fname() -> () {
+        assert_eq!("dir/image.manifest", sidecar_fname("dir/image.ppm"));
+        assert_eq!("image.manifest", sidecar_fname("image"));
+        assert_eq!("dir.d/image.manifest", sidecar_fname("dir.d/image"));
+    }
+}
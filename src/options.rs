@@ -6,182 +6,2435 @@ mod test_f64;
 mod test_string;
 mod test_u64;
 mod test_usize;
+mod test_view;
 
+use crate::bookmarks::{self, Bookmark};
 use crate::coord::Coord;
+use crate::error::Error;
+use crate::manifest::{self, Manifest};
+use crate::output;
+use crate::palette;
 use std::env;
 
 /// Stores fixed control parameters.
+#[derive(Clone)]
 pub struct Options {
+    /// Job to be done.
+    pub command: Command,
     /// Random seed which will be used to initialise random number generator.
     pub seed: u64,
     /// Inter-pixel distance of the final image.
     pub grid_size: f64,
     /// Number of pixels in the two dimensions.
     pub resols: Coord<usize>,
-    /// Name of the result image (should be `*.ppm`).
+    /// Name of the result image.
     pub fname: String,
+    /// Format of the result image.
+    pub format: Format,
+    /// Dithering applied when the colours are quantised to the bit depth.
+    pub dither: Dither,
+    /// Number of bits per sample, 8 or 16.
+    pub bit_depth: u8,
+    /// Whether the image has the alpha channel, with which the interior is transparent.
+    pub alpha: bool,
+    /// Name of the location picked from the catalogue, if given.
+    pub location: Option<String>,
+    /// Usage of the location.
+    pub location_as: LocationAs,
+    /// Name of the user bookmark file, if given.
+    pub bookmarks: Option<String>,
+    /// Name of the history file of the past renders, if given.
+    pub history: Option<String>,
+    /// Name of the raw data file of the per-pixel results, written by rendering or read by recolouring, if given.
+    pub data: Option<String>,
+    /// Number of the latest renders kept when the history is pruned.
+    pub keep: usize,
+    /// Center of the final image, with which the search is skipped if given.
+    pub center: Option<Coord<f64>>,
+    /// Strategy to find a nice image center.
+    pub search: Search,
+    /// Lower-left and upper-right corners of the region from which the search starts.
+    pub region: [Coord<f64>; 2],
+    /// Strategy to draw the initial center of the search.
+    pub sampling: Sampling,
+    /// Whether the search is forbidden to drift outside `region`.
+    pub confine: bool,
+    /// Minimum zoom which the search should reach, if given.
+    pub min_zoom: Option<f64>,
+    /// Maximum zoom beyond which the search does not go, if given.
+    pub max_zoom: Option<f64>,
+    /// Maximum fraction of the interior pixels of an acceptable image.
+    pub max_interior: f64,
+    /// Minimum colour variance of an acceptable image.
+    pub min_colour_variance: f64,
+    /// Minimum fraction of the edge pixels of an acceptable image.
+    pub min_edge_density: f64,
+    /// Minimum spread of the escape-count histogram of an acceptable image.
+    pub min_histogram_spread: f64,
+    /// Number of times the search is retried with the next seed when the image is rejected.
+    pub retries: usize,
+    /// Rotation angle of the image around its center in radians.
+    pub rotation: f64,
+    /// Name of a built-in palette or a palette file.
+    pub palette: String,
+    /// Treatment of the positions outside a gradient palette.
+    pub palette_mode: PaletteMode,
+    /// Phase shift of the colour palette in cycles.
+    pub palette_offset: f64,
+    /// Number of times the colour palette is repeated.
+    pub palette_repeat: f64,
+    /// Colour space in which gradient palettes are interpolated.
+    pub colour_space: ColourSpace,
+    /// Colour vision deficiency simulated on the result, if given.
+    pub simulate: Option<Deficiency>,
+    /// Function mapping the number of iterations to the position on the palette.
+    pub transfer: Transfer,
+    /// Exponent of the power transfer function.
+    pub transfer_exponent: f64,
+    /// Steepness of the arctangent transfer function.
+    pub transfer_steepness: f64,
+    /// Lower and upper percentiles of the number of iterations of the exterior mapped to the ends of the palette, if given.
+    pub clip: Option<[f64; 2]>,
+    /// Height field with which the colours are shaded as a relief, if given.
+    pub relief: Option<Relief>,
+    /// Direction of the light on the image in degrees, counter-clockwise from the right.
+    pub light_azimuth: f64,
+    /// Angle of the light above the image in degrees.
+    pub light_elevation: f64,
+    /// Brightness of the relief in the shade.
+    pub ambient: f64,
+    /// Intensity of the specular highlights of the relief.
+    pub specular: f64,
+    /// Factor exaggerating the slopes of the relief.
+    pub relief_strength: f64,
+    /// Weight of the shaded colours blended with the palette colours.
+    pub relief_blend: f64,
+    /// Name of the keyframe file to render an animation, if given.
+    pub keyframes: Option<String>,
+    /// Number of frames per unit time of the animation.
+    pub fps: f64,
+    /// Number of threads to render frames of the animation concurrently.
+    pub threads: usize,
+    /// Index of the frame from which the animation is (re)started.
+    pub resume_from: usize,
+    /// Resolutions of the images rendered at the found center, one image per size.
+    pub sizes: Vec<Coord<usize>>,
+    /// Quantity kept among the sizes.
+    pub size_scale: SizeScale,
+    /// Name of the layout file to split the view into several screens, if given.
+    pub layout: Option<String>,
+}
+
+/// Jobs which can be requested as the first command-line argument.
+#[derive(Clone)]
+pub enum Command {
+    /// renders an image (default)
+    Render,
+    /// prints the catalogue of locations
+    List,
+    /// prints the history of the past renders
+    History,
+    /// drops the past renders except the latest ones
+    Prune,
+    /// colours the results stored in a raw data file again
+    Recolor,
+    /// prints the metadata embedded in an image
+    Info,
+}
+
+/// Usages of a location picked from the catalogue.
+#[derive(Clone)]
+pub enum LocationAs {
+    /// rendered directly
+    View,
+    /// used as the region from which the search starts
+    Region,
+}
+
+impl std::str::FromStr for LocationAs {
+    type Err = ();
+    fn from_str(s: &str) -> Result<LocationAs, ()> {
+        return match s {
+            "view" => Ok(LocationAs::View),
+            "region" => Ok(LocationAs::Region),
+            _ => Err(()),
+        };
+    }
+}
+
+impl std::fmt::Display for LocationAs {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name: &str = match self {
+            LocationAs::View => "view",
+            LocationAs::Region => "region",
+        };
+        return write!(f, "{}", name);
+    }
+}
+
+/// Formats of the result image.
+#[derive(Clone)]
+pub enum Format {
+    /// binary Portable PixMap
+    Ppm,
+    /// binary Portable GrayMap of the iteration map
+    Pgm,
+    /// Portable Arbitrary Map
+    Pam,
+    /// Portable Network Graphics
+    Png,
+    /// Windows bitmap
+    Bmp,
+    /// Truevision TGA
+    Tga,
+    /// Quite OK Image
+    Qoi,
+    /// farbfeld
+    Farbfeld,
+    /// Portable FloatMap, 32-bit floating-point RGB
+    Pfm,
+}
+
+impl Format {
+    /// Checks whether the format can store the samples.
+    ///
+    /// * `bit_depth` - Number of bits per sample.
+    /// * `alpha`     - Whether the alpha channel is requested.
+    pub fn supports(&self, bit_depth: u8, alpha: bool) -> bool {
+        return match self {
+            Format::Ppm | Format::Pgm => !alpha,
+            Format::Pam | Format::Png => true,
+            Format::Bmp | Format::Tga | Format::Qoi => 8 == bit_depth,
+            // always 16-bit RGBA
+            Format::Farbfeld => true,
+            // always floating-point RGB, the bit depth is irrelevant
+            Format::Pfm => !alpha,
+        };
+    }
+}
+
+impl std::str::FromStr for Format {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Format, ()> {
+        return match s {
+            "ppm" => Ok(Format::Ppm),
+            "pgm" => Ok(Format::Pgm),
+            "pam" => Ok(Format::Pam),
+            "png" => Ok(Format::Png),
+            "bmp" => Ok(Format::Bmp),
+            "tga" => Ok(Format::Tga),
+            "qoi" => Ok(Format::Qoi),
+            "ff" => Ok(Format::Farbfeld),
+            "pfm" => Ok(Format::Pfm),
+            _ => Err(()),
+        };
+    }
+}
+
+impl std::fmt::Display for Format {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name: &str = match self {
+            Format::Ppm => "ppm",
+            Format::Pgm => "pgm",
+            Format::Pam => "pam",
+            Format::Png => "png",
+            Format::Bmp => "bmp",
+            Format::Tga => "tga",
+            Format::Qoi => "qoi",
+            Format::Farbfeld => "ff",
+            Format::Pfm => "pfm",
+        };
+        return write!(f, "{}", name);
+    }
+}
+
+/// Treatments of the positions outside a gradient palette.
+#[derive(Clone)]
+pub enum PaletteMode {
+    /// wrapping around
+    Cyclic,
+    /// clipped to the ends
+    Clamped,
+}
+
+impl std::str::FromStr for PaletteMode {
+    type Err = ();
+    fn from_str(s: &str) -> Result<PaletteMode, ()> {
+        return match s {
+            "cyclic" => Ok(PaletteMode::Cyclic),
+            "clamped" => Ok(PaletteMode::Clamped),
+            _ => Err(()),
+        };
+    }
+}
+
+impl std::fmt::Display for PaletteMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name: &str = match self {
+            PaletteMode::Cyclic => "cyclic",
+            PaletteMode::Clamped => "clamped",
+        };
+        return write!(f, "{}", name);
+    }
+}
+
+/// Colour spaces in which gradient palettes are interpolated.
+#[derive(Clone)]
+pub enum ColourSpace {
+    /// gamma-encoded sRGB
+    Srgb,
+    /// linear sRGB
+    Linear,
+    /// OKLab
+    Oklab,
+    /// OKLCh, the polar form of OKLab
+    Oklch,
+}
+
+impl std::str::FromStr for ColourSpace {
+    type Err = ();
+    fn from_str(s: &str) -> Result<ColourSpace, ()> {
+        return match s {
+            "srgb" => Ok(ColourSpace::Srgb),
+            "linear" => Ok(ColourSpace::Linear),
+            "oklab" => Ok(ColourSpace::Oklab),
+            "oklch" => Ok(ColourSpace::Oklch),
+            _ => Err(()),
+        };
+    }
+}
+
+impl std::fmt::Display for ColourSpace {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name: &str = match self {
+            ColourSpace::Srgb => "srgb",
+            ColourSpace::Linear => "linear",
+            ColourSpace::Oklab => "oklab",
+            ColourSpace::Oklch => "oklch",
+        };
+        return write!(f, "{}", name);
+    }
+}
+
+/// Colour vision deficiencies which can be simulated.
+#[derive(Clone)]
+pub enum Deficiency {
+    /// lack of the long-wavelength (red) cones
+    Protanopia,
+    /// lack of the medium-wavelength (green) cones
+    Deuteranopia,
+    /// lack of the short-wavelength (blue) cones
+    Tritanopia,
+}
+
+impl std::str::FromStr for Deficiency {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Deficiency, ()> {
+        return match s {
+            "protanopia" => Ok(Deficiency::Protanopia),
+            "deuteranopia" => Ok(Deficiency::Deuteranopia),
+            "tritanopia" => Ok(Deficiency::Tritanopia),
+            _ => Err(()),
+        };
+    }
+}
+
+impl std::fmt::Display for Deficiency {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name: &str = match self {
+            Deficiency::Protanopia => "protanopia",
+            Deficiency::Deuteranopia => "deuteranopia",
+            Deficiency::Tritanopia => "tritanopia",
+        };
+        return write!(f, "{}", name);
+    }
+}
+
+/// Functions mapping the number of iterations to the position on the palette.
+#[derive(Clone)]
+pub enum Transfer {
+    /// proportional
+    Linear,
+    /// logarithm
+    Log,
+    /// square root
+    Sqrt,
+    /// power with an exponent
+    Power,
+    /// arctangent with a steepness
+    Arctan,
+    /// cumulative distribution, giving a flat histogram
+    Equalise,
+}
+
+impl std::str::FromStr for Transfer {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Transfer, ()> {
+        return match s {
+            "linear" => Ok(Transfer::Linear),
+            "log" => Ok(Transfer::Log),
+            "sqrt" => Ok(Transfer::Sqrt),
+            "power" => Ok(Transfer::Power),
+            "arctan" => Ok(Transfer::Arctan),
+            "equalise" | "equalize" => Ok(Transfer::Equalise),
+            _ => Err(()),
+        };
+    }
+}
+
+impl std::fmt::Display for Transfer {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name: &str = match self {
+            Transfer::Linear => "linear",
+            Transfer::Log => "log",
+            Transfer::Sqrt => "sqrt",
+            Transfer::Power => "power",
+            Transfer::Arctan => "arctan",
+            Transfer::Equalise => "equalise",
+        };
+        return write!(f, "{}", name);
+    }
+}
+
+/// Height fields with which the colours are shaded as a relief.
+#[derive(Clone)]
+pub enum Relief {
+    /// continuous number of iterations
+    Smooth,
+    /// logarithm of the distance estimate
+    Distance,
+}
+
+impl std::str::FromStr for Relief {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Relief, ()> {
+        return match s {
+            "smooth" => Ok(Relief::Smooth),
+            "distance" => Ok(Relief::Distance),
+            _ => Err(()),
+        };
+    }
+}
+
+impl std::fmt::Display for Relief {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name: &str = match self {
+            Relief::Smooth => "smooth",
+            Relief::Distance => "distance",
+        };
+        return write!(f, "{}", name);
+    }
+}
+
+/// Quantities kept when the same view is rendered at several resolutions.
+#[derive(Clone)]
+pub enum SizeScale {
+    /// field of view, fitted to each aspect ratio
+    View,
+    /// distance between two adjacent pixels
+    Spacing,
+}
+
+impl std::str::FromStr for SizeScale {
+    type Err = ();
+    fn from_str(s: &str) -> Result<SizeScale, ()> {
+        return match s {
+            "view" => Ok(SizeScale::View),
+            "spacing" => Ok(SizeScale::Spacing),
+            _ => Err(()),
+        };
+    }
+}
+
+impl std::fmt::Display for SizeScale {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name: &str = match self {
+            SizeScale::View => "view",
+            SizeScale::Spacing => "spacing",
+        };
+        return write!(f, "{}", name);
+    }
+}
+
+/// Dithering methods applied when the colours are quantised.
+#[derive(Clone)]
+pub enum Dither {
+    /// rounding to the nearest level
+    None,
+    /// Bayer matrix
+    Ordered,
+    /// blue-noise mask
+    BlueNoise,
+    /// Floyd-Steinberg error diffusion
+    FloydSteinberg,
+}
+
+impl std::str::FromStr for Dither {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Dither, ()> {
+        return match s {
+            "none" => Ok(Dither::None),
+            "ordered" => Ok(Dither::Ordered),
+            "blue_noise" => Ok(Dither::BlueNoise),
+            "floyd_steinberg" => Ok(Dither::FloydSteinberg),
+            _ => Err(()),
+        };
+    }
+}
+
+impl std::fmt::Display for Dither {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name: &str = match self {
+            Dither::None => "none",
+            Dither::Ordered => "ordered",
+            Dither::BlueNoise => "blue_noise",
+            Dither::FloydSteinberg => "floyd_steinberg",
+        };
+        return write!(f, "{}", name);
+    }
+}
+
+/// Strategies to find a nice image center.
+#[derive(Clone)]
+pub enum Search {
+    /// zooming into the most complex quadrant repeatedly
+    Quadrant,
+    /// perturbing the center and the zoom by simulated annealing
+    Anneal,
+}
+
+impl std::str::FromStr for Search {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Search, ()> {
+        return match s {
+            "quadrant" => Ok(Search::Quadrant),
+            "anneal" => Ok(Search::Anneal),
+            _ => Err(()),
+        };
+    }
+}
+
+impl std::fmt::Display for Search {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name: &str = match self {
+            Search::Quadrant => "quadrant",
+            Search::Anneal => "anneal",
+        };
+        return write!(f, "{}", name);
+    }
+}
+
+/// Strategies to draw the initial center of the search.
+#[derive(Clone)]
+pub enum Sampling {
+    /// uniformly inside the region
+    Uniform,
+    /// uniformly inside the region, while rejecting those far from the set boundary
+    Boundary,
+    /// following the Halton sequence, while rejecting those far from the set boundary
+    Halton,
+}
+
+impl std::str::FromStr for Sampling {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Sampling, ()> {
+        return match s {
+            "uniform" => Ok(Sampling::Uniform),
+            "boundary" => Ok(Sampling::Boundary),
+            "halton" => Ok(Sampling::Halton),
+            _ => Err(()),
+        };
+    }
+}
+
+impl std::fmt::Display for Sampling {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name: &str = match self {
+            Sampling::Uniform => "uniform",
+            Sampling::Boundary => "boundary",
+            Sampling::Halton => "halton",
+        };
+        return write!(f, "{}", name);
+    }
+}
+
+impl Default for Options {
+    /// Gives the default parameters, which are used in the absence of the user specification.
+    fn default() -> Options {
+        return Options {
+            command: Command::Render,
+            seed: 0u64,
+            grid_size: 5.0e-7f64,
+            resols: Coord {
+                x: 1280usize,
+                y: 800usize,
+            },
+            fname: String::from("image.ppm"),
+            format: Format::Ppm,
+            bit_depth: 8u8,
+            dither: Dither::None,
+            alpha: false,
+            location: None,
+            location_as: LocationAs::View,
+            bookmarks: None,
+            history: None,
+            data: None,
+            keep: 30usize,
+            center: None,
+            search: Search::Quadrant,
+            region: [Coord { x: -1., y: -1. }, Coord { x: 1., y: 1. }],
+            sampling: Sampling::Boundary,
+            confine: false,
+            min_zoom: None,
+            max_zoom: None,
+            max_interior: 1f64,
+            min_colour_variance: 0f64,
+            min_edge_density: 0f64,
+            min_histogram_spread: 0f64,
+            retries: 4usize,
+            rotation: 0f64,
+            palette: String::from("rainbow"),
+            palette_mode: PaletteMode::Cyclic,
+            palette_offset: 0f64,
+            palette_repeat: 1f64,
+            colour_space: ColourSpace::Srgb,
+            simulate: None,
+            transfer: Transfer::Linear,
+            transfer_exponent: 0.5f64,
+            transfer_steepness: 10f64,
+            clip: None,
+            relief: None,
+            light_azimuth: 45f64,
+            light_elevation: 45f64,
+            ambient: 0.3f64,
+            specular: 0.3f64,
+            relief_strength: 1f64,
+            relief_blend: 1f64,
+            keyframes: None,
+            fps: 30f64,
+            threads: match std::thread::available_parallelism() {
+                Ok(threads) => threads.get(),
+                Err(_) => 1usize,
+            },
+            resume_from: 0usize,
+            sizes: Vec::new(),
+            size_scale: SizeScale::View,
+            layout: None,
+        };
+    }
 }
 
 /// Loads and validates the control parameters.
-pub fn init() -> Result<Options, ()> {
+pub fn init() -> Result<Options, Error> {
     // create an Options instance with default parameters
-    let mut options: Options = Options {
-        seed: 0u64,
-        grid_size: 5.0e-7f64,
-        resols: Coord {
-            x: 1280usize,
-            y: 800usize,
-        },
-        fname: String::from("image.ppm"),
+    let mut options: Options = Options::default();
+    // load parameters
+    let mut args: Vec<String> = env::args().collect();
+    // parameters recorded in a manifest are appended,
+    //   so that those given explicitly take precedence
+    match load_from_manifest(&args) {
+        Ok(records) => args.extend(records),
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    // the first argument may specify a job other than rendering
+    options.command = match load_command(&args) {
+        Ok(command) => command,
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    // update default parameters if exist
+    options.seed = match load_seed(&args, options.seed) {
+        Ok(seed) => seed,
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    options.grid_size = match load_grid_size(&args, options.grid_size) {
+        Ok(grid_size) => grid_size,
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    options.resols = match load_resols(&args, options.resols) {
+        Ok(resols) => resols,
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    options.fname = match load_fname(&args, options.fname) {
+        Ok(fname) => fname,
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    options.format = match load_format(&args, &options.fname) {
+        Ok(format) => format,
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    (options.bit_depth, options.alpha) =
+        match load_samples(&args, &options.format, (options.bit_depth, options.alpha)) {
+            Ok(samples) => samples,
+            Err(error) => {
+                print_error_message();
+                return Err(error);
+            }
+        };
+    options.dither = match load_dither(&args, options.dither) {
+        Ok(dither) => dither,
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    options.rotation = match load_rotation(&args, options.rotation) {
+        Ok(rotation) => rotation,
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    options.bookmarks = match load_bookmarks(&args, options.bookmarks) {
+        Ok(bookmarks) => bookmarks,
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    options.location_as = match load_location_as(&args, options.location_as) {
+        Ok(location_as) => location_as,
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    options.history = match load_history(&args, options.history) {
+        Ok(history) => history,
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    options.data = match load_data(&args, options.data) {
+        Ok(data) => data,
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    options.keep = match load_keep(&args, options.keep) {
+        Ok(keep) => keep,
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    match options.command {
+        Command::Render => {}
+        Command::List => return Ok(options),
+        Command::History | Command::Prune => {
+            if options.history.is_none() {
+                print_error_message();
+                return Err(Error::Argument(String::from(
+                    "history: expect a history file for this command",
+                )));
+            }
+            return Ok(options);
+        }
+        // the image is given as the second argument or by fname
+        Command::Info => {
+            if let Some(image) = args.get(2).filter(|arg| !arg.starts_with("--")) {
+                options.fname = image.clone();
+            }
+            return Ok(options);
+        }
+        // the colouring options are loaded as usual
+        Command::Recolor => {
+            if options.data.is_none() {
+                print_error_message();
+                return Err(Error::Argument(String::from(
+                    "data: expect a raw data file for this command",
+                )));
+            }
+        }
+    };
+    // a location picked from the catalogue gives the defaults of the view or the region
+    let location: Option<Bookmark> = match load_location(&args, &options.bookmarks) {
+        Ok(location) => location,
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    if let Some(location) = location {
+        match options.location_as {
+            LocationAs::View => {
+                options.center = Some(location.center);
+                if !args.iter().any(|arg| arg.starts_with("--grid_size=")) {
+                    options.grid_size = location.span / options.resols.x as f64;
+                }
+            }
+            LocationAs::Region => {
+                options.region = [
+                    Coord {
+                        x: location.center.x - 0.5 * location.span,
+                        y: location.center.y - 0.5 * location.span,
+                    },
+                    Coord {
+                        x: location.center.x + 0.5 * location.span,
+                        y: location.center.y + 0.5 * location.span,
+                    },
+                ];
+            }
+        };
+        options.location = Some(location.name);
+    }
+    (options.center, options.grid_size) = match load_view(&args, &options) {
+        Ok(view) => view,
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    options.search = match load_search(&args, options.search) {
+        Ok(search) => search,
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    options.region = match load_region(&args, options.region) {
+        Ok(region) => region,
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    options.sampling = match load_sampling(&args, options.sampling) {
+        Ok(sampling) => sampling,
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    options.confine = match load_confine(&args, options.confine) {
+        Ok(confine) => confine,
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    (options.min_zoom, options.max_zoom) = match load_zoom_range(&args) {
+        Ok(zoom_range) => zoom_range,
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    [
+        options.max_interior,
+        options.min_colour_variance,
+        options.min_edge_density,
+        options.min_histogram_spread,
+    ] = match load_thresholds(
+        &args,
+        [
+            options.max_interior,
+            options.min_colour_variance,
+            options.min_edge_density,
+            options.min_histogram_spread,
+        ],
+    ) {
+        Ok(thresholds) => thresholds,
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    options.retries = match load_retries(&args, options.retries) {
+        Ok(retries) => retries,
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    options.palette = match load_palette(&args, options.palette) {
+        Ok(palette) => palette,
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    options.palette_mode = match load_palette_mode(&args, options.palette_mode) {
+        Ok(palette_mode) => palette_mode,
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    options.palette_repeat = match load_palette_repeat(&args, options.palette_repeat) {
+        Ok(palette_repeat) => palette_repeat,
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    options.colour_space = match load_colour_space(&args, options.colour_space) {
+        Ok(colour_space) => colour_space,
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    options.simulate = match load_simulate(&args, options.simulate) {
+        Ok(simulate) => simulate,
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    options.transfer = match load_transfer(&args, options.transfer) {
+        Ok(transfer) => transfer,
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    options.transfer_exponent = match load_transfer_exponent(&args, options.transfer_exponent) {
+        Ok(transfer_exponent) => transfer_exponent,
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    options.transfer_steepness = match load_transfer_steepness(&args, options.transfer_steepness) {
+        Ok(transfer_steepness) => transfer_steepness,
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    options.clip = match load_clip(&args, options.clip) {
+        Ok(clip) => clip,
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    options.relief = match load_relief(&args, options.relief) {
+        Ok(relief) => relief,
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    options.light_azimuth = match load_light_azimuth(&args, options.light_azimuth) {
+        Ok(light_azimuth) => light_azimuth,
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    options.light_elevation = match load_light_elevation(&args, options.light_elevation) {
+        Ok(light_elevation) => light_elevation,
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    options.ambient = match load_ambient(&args, options.ambient) {
+        Ok(ambient) => ambient,
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    options.specular = match load_specular(&args, options.specular) {
+        Ok(specular) => specular,
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    options.relief_strength = match load_relief_strength(&args, options.relief_strength) {
+        Ok(relief_strength) => relief_strength,
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    options.relief_blend = match load_relief_blend(&args, options.relief_blend) {
+        Ok(relief_blend) => relief_blend,
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    options.palette_offset = match load_palette_offset(&args, options.palette_offset) {
+        Ok(palette_offset) => palette_offset,
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    options.keyframes = match load_keyframes(&args, options.keyframes) {
+        Ok(keyframes) => keyframes,
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    // each frame is written to its own file
+    if options.keyframes.is_some() && output::STDOUT == options.fname {
+        print_error_message();
+        return Err(Error::Argument(String::from(
+            "fname: the standard output is not available for animations",
+        )));
+    }
+    options.fps = match load_fps(&args, options.fps) {
+        Ok(fps) => fps,
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    options.threads = match load_threads(&args, options.threads) {
+        Ok(threads) => threads,
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    options.resume_from = match load_resume_from(&args, options.resume_from) {
+        Ok(resume_from) => resume_from,
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    options.sizes = match load_sizes(&args, options.sizes) {
+        Ok(sizes) => sizes,
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    // each size is written to its own file
+    if !options.sizes.is_empty() && output::STDOUT == options.fname {
+        print_error_message();
+        return Err(Error::Argument(String::from(
+            "fname: the standard output is not available for several sizes",
+        )));
+    }
+    if !options.sizes.is_empty() && options.keyframes.is_some() {
+        print_error_message();
+        return Err(Error::Argument(String::from(
+            "sizes: not available for animations",
+        )));
+    }
+    options.size_scale = match load_size_scale(&args, options.size_scale) {
+        Ok(size_scale) => size_scale,
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    options.layout = match load_layout(&args, options.layout) {
+        Ok(layout) => layout,
+        Err(error) => {
+            print_error_message();
+            return Err(error);
+        }
+    };
+    // each screen is written to its own file
+    if options.layout.is_some() && output::STDOUT == options.fname {
+        print_error_message();
+        return Err(Error::Argument(String::from(
+            "fname: the standard output is not available for layouts",
+        )));
+    }
+    if options.layout.is_some() && (options.keyframes.is_some() || !options.sizes.is_empty()) {
+        print_error_message();
+        return Err(Error::Argument(String::from(
+            "layout: not available for animations or several sizes",
+        )));
+    }
+    print_last_message(&options);
+    return Ok(options);
+}
+
+/// Outputs an error message before abort.
+fn print_error_message() -> () {
+    eprintln!("ERROR: failed to load command-line arguments.");
+    eprintln!("USAGE: cargo run -- [command] --<key>=<value>");
+    eprintln!("Available commands are listed below.");
+    eprintln!("    (none)         : render an image");
+    eprintln!("    list           : print the catalogue of locations");
+    eprintln!("    history        : print the history of the past renders");
+    eprintln!("    prune          : drop the past renders except the latest ones");
+    eprintln!("    recolor        : colour the results stored in a raw data file again");
+    eprintln!("    info <image>   : print the metadata embedded in an image");
+    eprintln!("Available keys and their types / requirements are listed below.");
+    eprintln!("    seed           : positive integer (u64)");
+    eprintln!("    grid_size      : positive floating number (f64)");
+    eprintln!("    width          : positive integer number (usize)");
+    eprintln!("    height         : positive integer number (usize)");
+    eprintln!("    fname          : string which ends with a suffix of the formats below");
+    eprintln!("    format         : ppm, pgm, pam, png, bmp, tga, qoi, ff, or pfm");
+    eprintln!("    bit_depth      : 8 or 16, 16 is for ppm, pgm, pam, png, and ff, ignored by pfm");
+    eprintln!(
+        "    alpha          : true or false (bool), true is for pam, png, bmp, tga, qoi, and ff"
+    );
+    eprintln!("    dither         : none, ordered, blue_noise, or floyd_steinberg");
+    eprintln!("    rotation       : floating number (f64)");
+    eprintln!("    location       : name of a location in the catalogue (string)");
+    eprintln!("    location_as    : view or region");
+    eprintln!("    bookmarks      : name of a user bookmark file (string)");
+    eprintln!("    history        : name of a history file (string)");
+    eprintln!("    keep           : non-negative integer number (usize)");
+    eprintln!("    data           : name of a raw data file (string), .npy for NumPy");
+    eprintln!("    center_x       : floating number (f64)");
+    eprintln!("    center_y       : floating number (f64)");
+    eprintln!("    xmin           : floating number (f64)");
+    eprintln!("    xmax           : floating number (f64)");
+    eprintln!("    ymin           : floating number (f64)");
+    eprintln!("    ymax           : floating number (f64)");
+    eprintln!("    zoom_rect      : four comma-separated pixel positions (f64)");
+    eprintln!(
+        "    region         : four comma-separated floating numbers xmin,xmax,ymin,ymax (f64)"
+    );
+    eprintln!("    sampling       : uniform, boundary, or halton");
+    eprintln!("    confine        : true or false (bool)");
+    eprintln!("    min_zoom       : positive floating number (f64)");
+    eprintln!("    max_zoom       : positive floating number (f64)");
+    eprintln!("    max_interior   : floating number in [0:1] (f64)");
+    eprintln!("    min_colour_variance  : floating number in [0:1] (f64)");
+    eprintln!("    min_edge_density     : floating number in [0:1] (f64)");
+    eprintln!("    min_histogram_spread : floating number in [0:1] (f64)");
+    eprintln!("    retries        : non-negative integer number (usize)");
+    eprintln!(
+        "    palette        : rainbow, grey, fire, ocean, ultra, viridis, magma, inferno, cividis,"
+    );
+    eprintln!("                     or a .ggr, .map, or .csv file");
+    eprintln!("    palette_mode   : cyclic or clamped");
+    eprintln!("    palette_offset : floating number (f64)");
+    eprintln!("    palette_repeat : positive floating number (f64)");
+    eprintln!("    colour_space   : srgb, linear, oklab, or oklch");
+    eprintln!("    simulate       : protanopia, deuteranopia, or tritanopia");
+    eprintln!("    transfer       : linear, log, sqrt, power, arctan, or equalise");
+    eprintln!("    transfer_exponent  : positive floating number (f64)");
+    eprintln!("    transfer_steepness : positive floating number (f64)");
+    eprintln!("    clip           : two comma-separated percentiles lower,upper in [0:100] (f64)");
+    eprintln!("    relief         : smooth or distance");
+    eprintln!("    light_azimuth  : floating number in degrees (f64)");
+    eprintln!("    light_elevation : floating number in [0:90] degrees (f64)");
+    eprintln!("    ambient        : floating number in [0:1] (f64)");
+    eprintln!("    specular       : non-negative floating number (f64)");
+    eprintln!("    relief_strength : positive floating number (f64)");
+    eprintln!("    relief_blend   : floating number in [0:1] (f64)");
+    eprintln!("    keyframes      : name of a keyframe file (string)");
+    eprintln!("    fps            : positive floating number (f64)");
+    eprintln!("    threads        : positive integer number (usize)");
+    eprintln!("    resume_from    : non-negative integer number (usize)");
+    eprintln!("    sizes          : comma-separated resolutions, e.g. 1280x800,2560x1600");
+    eprintln!("    size_scale     : view or spacing");
+    eprintln!("    layout         : name of a layout file (string)");
+    eprintln!("    from_manifest  : name of a manifest file (string)");
+    eprintln!("See also README.rst.");
+}
+
+/// Outputs an log message before return.
+///   
+/// * `options` - Parameters loaded and / or assigned.
+fn print_last_message(options: &Options) -> () {
+    eprintln!("The settings are configured as follows.");
+    eprintln!("    random seed     : {}", options.seed);
+    eprintln!("    grid size       : {}", options.grid_size);
+    eprintln!("    width           : {}", options.resols.x);
+    eprintln!("    height          : {}", options.resols.y);
+    eprintln!("    image file name : {}", options.fname);
+    eprintln!("    image format    : {}", options.format);
+    eprintln!("    bit depth       : {}", options.bit_depth);
+    eprintln!("    alpha channel   : {}", options.alpha);
+    eprintln!("    dithering       : {}", options.dither);
+    eprintln!("    rotation        : {}", options.rotation);
+    if let Some(location) = &options.location {
+        eprintln!(
+            "    location        : {} as {}",
+            location, options.location_as
+        );
+    }
+    match &options.center {
+        Some(center) => eprintln!(
+            "    center          : ({:+.15e}, {:+.15e})",
+            center.x, center.y
+        ),
+        None => {
+            eprintln!("    center          : to be searched");
+            eprintln!("    search          : {}", options.search);
+            eprintln!(
+                "    search region   : [{}, {}] x [{}, {}]",
+                options.region[0].x, options.region[1].x, options.region[0].y, options.region[1].y
+            );
+            eprintln!("    sampling        : {}", options.sampling);
+            eprintln!("    confined        : {}", options.confine);
+            if let Some(min_zoom) = options.min_zoom {
+                eprintln!("    minimum zoom    : {}", min_zoom);
+            }
+            if let Some(max_zoom) = options.max_zoom {
+                eprintln!("    maximum zoom    : {}", max_zoom);
+            }
+            eprintln!("    retries         : {}", options.retries);
+            if let Some(history) = &options.history {
+                eprintln!("    history file    : {}", history);
+            }
+        }
+    };
+    if let Some(data) = &options.data {
+        eprintln!("    raw data file   : {}", data);
+    }
+    eprintln!("    palette         : {}", options.palette);
+    eprintln!("    palette mode    : {}", options.palette_mode);
+    eprintln!("    palette offset  : {}", options.palette_offset);
+    eprintln!("    palette repeat  : {}", options.palette_repeat);
+    eprintln!("    colour space    : {}", options.colour_space);
+    if let Some(simulate) = &options.simulate {
+        eprintln!("    simulated as    : {}", simulate);
+    }
+    match options.transfer {
+        Transfer::Power => eprintln!(
+            "    transfer        : {} ({})",
+            options.transfer, options.transfer_exponent
+        ),
+        Transfer::Arctan => eprintln!(
+            "    transfer        : {} ({})",
+            options.transfer, options.transfer_steepness
+        ),
+        _ => eprintln!("    transfer        : {}", options.transfer),
+    };
+    if let Some(clip) = options.clip {
+        eprintln!("    clip            : {}% - {}%", clip[0], clip[1]);
+    }
+    if let Some(relief) = &options.relief {
+        eprintln!(
+            "    relief          : {} (strength {}, blend {})",
+            relief, options.relief_strength, options.relief_blend
+        );
+        eprintln!(
+            "    light           : azimuth {}, elevation {}",
+            options.light_azimuth, options.light_elevation
+        );
+        eprintln!(
+            "    reflection      : ambient {}, specular {}",
+            options.ambient, options.specular
+        );
+    }
+    if let Some(keyframes) = &options.keyframes {
+        eprintln!("    keyframe file   : {}", keyframes);
+        eprintln!("    frames / time   : {}", options.fps);
+        eprintln!("    threads         : {}", options.threads);
+        eprintln!("    resume from     : {}", options.resume_from);
+    }
+    if !options.sizes.is_empty() {
+        let sizes: Vec<String> = options
+            .sizes
+            .iter()
+            .map(|size| format!("{}x{}", size.x, size.y))
+            .collect();
+        eprintln!("    sizes           : {}", sizes.join(", "));
+        eprintln!("    size scale      : {}", options.size_scale);
+    }
+    if let Some(layout) = &options.layout {
+        eprintln!("    layout file     : {}", layout);
+    }
+}
+
+/// Loads the records of a manifest file and converts them to command-line arguments.
+///   
+/// When the resolution is given explicitly but the grid size is not,
+/// the grid size is adjusted so that the recorded field of view is kept.
+///   
+/// * `args` - All command-line arguments as a vector of strings.
+fn load_from_manifest(args: &[String]) -> Result<Vec<String>, Error> {
+    const KEY: &str = "from_manifest";
+    let fname: String = match extract_optional_value::<String>(KEY, args) {
+        Ok(Some(fname)) => fname,
+        Ok(None) => return Ok(Vec::new()),
+        Err(msg) => {
+            return Err(Error::Argument(format!("{}: {}", KEY, msg)));
+        }
+    };
+    let manifest: Manifest = Manifest::load(&fname)?;
+    return manifest_args(args, &manifest);
+}
+
+/// Converts the records of a manifest to command-line arguments.
+///   
+/// The output files (`fname`, `format`, and `data`) are not taken from the manifest,
+/// so that the original image is never overwritten unless it is given explicitly.
+/// The recorded center is dropped when a rectangle is given explicitly, which replaces it.
+///   
+/// * `args`     - All command-line arguments as a vector of strings.
+/// * `manifest` - Loaded manifest.
+fn manifest_args(args: &[String], manifest: &Manifest) -> Result<Vec<String>, Error> {
+    const KEY: &str = "from_manifest";
+    const OUTPUTS: [&str; 3] = ["fname", "format", "data"];
+    const CENTER: [&str; 2] = ["center_x", "center_y"];
+    const RECT: [&str; 4] = ["xmin", "xmax", "ymin", "ymax"];
+    let mut records: Vec<String> = Vec::new();
+    // keep the field of view
+    let is_resized: bool = args
+        .iter()
+        .any(|arg| arg.starts_with("--width=") || arg.starts_with("--height="));
+    let is_rescaled: bool = args.iter().any(|arg| arg.starts_with("--grid_size="));
+    if is_resized && !is_rescaled {
+        let grid_size: Option<f64> = manifest.get("grid_size").and_then(|v| v.parse().ok());
+        let width: Option<usize> = manifest.get("width").and_then(|v| v.parse().ok());
+        let height: Option<usize> = manifest.get("height").and_then(|v| v.parse().ok());
+        let (grid_size, old): (f64, Coord<usize>) = match (grid_size, width, height) {
+            (Some(grid_size), Some(x), Some(y)) => (grid_size, Coord { x, y }),
+            _ => {
+                return Err(Error::Argument(format!(
+                    "{}: expect valid grid_size, width, and height",
+                    KEY
+                )));
+            }
+        };
+        let new: Coord<usize> = load_resols(args, old)?;
+        let grid_size: f64 = f64::max(
+            (old.x as f64 * grid_size) / new.x as f64,
+            (old.y as f64 * grid_size) / new.y as f64,
+        );
+        records.push(format!("--grid_size={}", grid_size));
+    }
+    let is_rect: bool = args.iter().any(|arg| {
+        RECT.iter()
+            .any(|key| arg.starts_with(&format!("--{}=", key)))
+    });
+    for (key, value) in manifest.records.iter() {
+        if OUTPUTS.contains(&key.as_str()) || manifest::is_informative(key) {
+            continue;
+        }
+        if is_rect && CENTER.contains(&key.as_str()) {
+            continue;
+        }
+        records.push(format!("--{}={}", key, value));
+    }
+    return Ok(records);
+}
+
+/// Loads a random seed from the command-line arguments and try to interpret it as a `u64` value.
+///   
+/// * `args`    - All command-line arguments as a vector of strings.
+/// * `default` - Default value filled in the absence of the user specification.
+fn load_seed(args: &[String], default: u64) -> Result<u64, Error> {
+    const KEY: &str = "seed";
+    let value: u64 = match extract_value::<u64>(KEY, args, default) {
+        Ok(value) => value,
+        Err(msg) => {
+            return Err(Error::Argument(format!("{}: {}", KEY, msg)));
+        }
+    };
+    return Ok(value);
+}
+
+/// Loads a grid size from the command-line arguments and try to interpret it as a `f64` value.
+///   
+/// * `args`    - All command-line arguments as a vector of strings.
+/// * `default` - Default value filled in the absence of the user specification.
+fn load_grid_size(args: &[String], default: f64) -> Result<f64, Error> {
+    const KEY: &str = "grid_size";
+    let value: f64 = match extract_value::<f64>(KEY, args, default) {
+        Ok(value) => value,
+        Err(msg) => {
+            return Err(Error::Argument(format!("{}: {}", KEY, msg)));
+        }
+    };
+    // check being positive
+    if value > 0. {
+        return Ok(value);
+    } else {
+        return Err(Error::Argument(format!("{}: expect positive number", KEY)));
+    }
+}
+
+/// Loads `width` and `height` from the command-line arguments and try to interpret them as `usize` values.
+///   
+/// * `args`    - All command-line arguments as a vector of strings.
+/// * `default` - Default value filled in the absence of the user specification.
+fn load_resols(args: &[String], default: Coord<usize>) -> Result<Coord<usize>, Error> {
+    const KEYS: Coord<&str> = Coord {
+        x: "width",
+        y: "height",
+    };
+    let resols: Coord<usize> = Coord {
+        x: match extract_value::<usize>(KEYS.x, args, default.x) {
+            Ok(value) => value,
+            Err(msg) => {
+                return Err(Error::Argument(format!("{}: {}", KEYS.x, msg)));
+            }
+        },
+        y: match extract_value::<usize>(KEYS.y, args, default.y) {
+            Ok(value) => value,
+            Err(msg) => {
+                return Err(Error::Argument(format!("{}: {}", KEYS.y, msg)));
+            }
+        },
+    };
+    return Ok(resols);
+}
+
+/// Load the name of the final image from the command-line arguments.
+///   
+/// * `args`    - All command-line arguments as a vector of strings.
+/// * `default` - Default value filled in the absence of the user specification.
+fn load_fname(args: &[String], default: String) -> Result<String, Error> {
+    const KEY: &str = "fname";
+    let value: String = match extract_value::<String>(KEY, args, default) {
+        Ok(value) => value,
+        Err(msg) => {
+            return Err(Error::Argument(format!("{}: {}", KEY, msg)));
+        }
+    };
+    return Ok(value);
+}
+
+/// Loads the format of the final image from the command-line arguments,
+/// which is given by the suffix of the file name in the absence of the user specification.
+///   
+/// * `args`  - All command-line arguments as a vector of strings.
+/// * `fname` - Name of the final image.
+fn load_format(args: &[String], fname: &str) -> Result<Format, Error> {
+    const KEY: &str = "format";
+    let value: Option<Format> = match extract_optional_value::<Format>(KEY, args) {
+        Ok(value) => value,
+        Err(msg) => {
+            return Err(Error::Argument(format!("{}: {}", KEY, msg)));
+        }
+    };
+    if let Some(value) = value {
+        return Ok(value);
+    }
+    // the standard output has no suffix
+    if output::STDOUT == fname {
+        return Ok(Format::Ppm);
+    }
+    // check suffix
+    let suffix: &str = match fname.rsplit_once('.') {
+        Some((_, suffix)) => suffix,
+        None => "",
+    };
+    return match suffix.parse::<Format>() {
+        Ok(format) => Ok(format),
+        Err(_) => Err(Error::Argument(format!(
+            "fname: unknown suffix \"{}\", give a supported one or {}",
+            suffix, KEY
+        ))),
+    };
+}
+
+/// Loads the bit depth and the flag of the alpha channel from the command-line arguments,
+/// which should be supported by the format.
+///   
+/// * `args`    - All command-line arguments as a vector of strings.
+/// * `format`  - Format of the final image.
+/// * `default` - Default values filled in the absence of the user specification.
+fn load_samples(
+    args: &[String],
+    format: &Format,
+    default: (u8, bool),
+) -> Result<(u8, bool), Error> {
+    const KEYS: [&str; 2] = ["bit_depth", "alpha"];
+    let bit_depth: u8 = match extract_value::<u8>(KEYS[0], args, default.0) {
+        Ok(value) => value,
+        Err(msg) => {
+            return Err(Error::Argument(format!("{}: {}", KEYS[0], msg)));
+        }
+    };
+    if 8 != bit_depth && 16 != bit_depth {
+        return Err(Error::Argument(format!("{}: expect 8 or 16", KEYS[0])));
+    }
+    let alpha: bool = match extract_value::<bool>(KEYS[1], args, default.1) {
+        Ok(value) => value,
+        Err(msg) => {
+            return Err(Error::Argument(format!("{}: {}", KEYS[1], msg)));
+        }
+    };
+    if !format.supports(bit_depth, alpha) {
+        return Err(Error::Argument(format!(
+            "{}, {}: {}-bit samples {} alpha are not supported by {}",
+            KEYS[0],
+            KEYS[1],
+            bit_depth,
+            if alpha { "with" } else { "without" },
+            format
+        )));
+    }
+    return Ok((bit_depth, alpha));
+}
+
+/// Loads a rotation angle from the command-line arguments and try to interpret it as a `f64` value.
+///   
+/// * `args`    - All command-line arguments as a vector of strings.
+/// * `default` - Default value filled in the absence of the user specification.
+fn load_rotation(args: &[String], default: f64) -> Result<f64, Error> {
+    const KEY: &str = "rotation";
+    let value: f64 = match extract_value::<f64>(KEY, args, default) {
+        Ok(value) => value,
+        Err(msg) => {
+            return Err(Error::Argument(format!("{}: {}", KEY, msg)));
+        }
+    };
+    return Ok(value);
+}
+
+/// Loads the job to be done from the first command-line argument.
+///   
+/// * `args` - All command-line arguments as a vector of strings.
+fn load_command(args: &[String]) -> Result<Command, Error> {
+    const KEY: &str = "command";
+    let command: &str = match args.get(1) {
+        Some(command) => command,
+        None => return Ok(Command::Render),
+    };
+    if command.starts_with("--") {
+        return Ok(Command::Render);
+    }
+    return match command {
+        "list" => Ok(Command::List),
+        "history" => Ok(Command::History),
+        "prune" => Ok(Command::Prune),
+        "recolor" => Ok(Command::Recolor),
+        "info" => Ok(Command::Info),
+        _ => Err(Error::Argument(format!(
+            "{}: unknown command {}",
+            KEY, command
+        ))),
+    };
+}
+
+/// Loads the name of a user bookmark file from the command-line arguments.
+///   
+/// * `args`    - All command-line arguments as a vector of strings.
+/// * `default` - Default value filled in the absence of the user specification.
+fn load_bookmarks(args: &[String], default: Option<String>) -> Result<Option<String>, Error> {
+    const KEY: &str = "bookmarks";
+    let value: Option<String> = match extract_optional_value::<String>(KEY, args) {
+        Ok(value) => value,
+        Err(msg) => {
+            return Err(Error::Argument(format!("{}: {}", KEY, msg)));
+        }
+    };
+    return Ok(value.or(default));
+}
+
+/// Loads the name of a history file from the command-line arguments.
+///   
+/// * `args`    - All command-line arguments as a vector of strings.
+/// * `default` - Default value filled in the absence of the user specification.
+fn load_history(args: &[String], default: Option<String>) -> Result<Option<String>, Error> {
+    const KEY: &str = "history";
+    let value: Option<String> = match extract_optional_value::<String>(KEY, args) {
+        Ok(value) => value,
+        Err(msg) => {
+            return Err(Error::Argument(format!("{}: {}", KEY, msg)));
+        }
+    };
+    return Ok(value.or(default));
+}
+
+/// Loads the name of a raw data file from the command-line arguments.
+///   
+/// * `args`    - All command-line arguments as a vector of strings.
+/// * `default` - Default value filled in the absence of the user specification.
+fn load_data(args: &[String], default: Option<String>) -> Result<Option<String>, Error> {
+    const KEY: &str = "data";
+    let value: Option<String> = match extract_optional_value::<String>(KEY, args) {
+        Ok(value) => value,
+        Err(msg) => {
+            return Err(Error::Argument(format!("{}: {}", KEY, msg)));
+        }
+    };
+    return Ok(value.or(default));
+}
+
+/// Loads the number of the renders kept by pruning from the command-line arguments and try to interpret it as a `usize` value.
+///   
+/// * `args`    - All command-line arguments as a vector of strings.
+/// * `default` - Default value filled in the absence of the user specification.
+fn load_keep(args: &[String], default: usize) -> Result<usize, Error> {
+    const KEY: &str = "keep";
+    let value: usize = match extract_value::<usize>(KEY, args, default) {
+        Ok(value) => value,
+        Err(msg) => {
+            return Err(Error::Argument(format!("{}: {}", KEY, msg)));
+        }
+    };
+    return Ok(value);
+}
+
+/// Loads the usage of the location from the command-line arguments.
+///   
+/// * `args`    - All command-line arguments as a vector of strings.
+/// * `default` - Default value filled in the absence of the user specification.
+fn load_location_as(args: &[String], default: LocationAs) -> Result<LocationAs, Error> {
+    const KEY: &str = "location_as";
+    let value: LocationAs = match extract_value::<LocationAs>(KEY, args, default) {
+        Ok(value) => value,
+        Err(msg) => {
+            return Err(Error::Argument(format!("{}: {}", KEY, msg)));
+        }
+    };
+    return Ok(value);
+}
+
+/// Loads the name of a location from the command-line arguments and finds it in the catalogue.
+///   
+/// * `args`      - All command-line arguments as a vector of strings.
+/// * `bookmarks` - Name of the user bookmark file, if given.
+fn load_location(args: &[String], bookmarks: &Option<String>) -> Result<Option<Bookmark>, Error> {
+    const KEY: &str = "location";
+    let name: String = match extract_optional_value::<String>(KEY, args) {
+        Ok(Some(name)) => name,
+        Ok(None) => return Ok(None),
+        Err(msg) => {
+            return Err(Error::Argument(format!("{}: {}", KEY, msg)));
+        }
+    };
+    return match bookmarks::find(&name, bookmarks) {
+        Ok(bookmark) => Ok(Some(bookmark)),
+        Err(error) => Err(error),
+    };
+}
+
+/// Loads an explicit view of the complex plane from the command-line arguments.
+///   
+/// The view is given either by its center (`center_x` and `center_y`) together with `grid_size`,
+/// or by a rectangle (`xmin`, `xmax`, `ymin`, and `ymax`) which is fitted to the aspect ratio of the image.
+/// Otherwise, the center already stored in `options` (given by a location) is kept.
+/// In addition, `zoom_rect` picks a rectangle in pixels from the view to give the next view.
+/// Returns the center (if given) and the grid size.
+///   
+/// * `args`    - All command-line arguments as a vector of strings.
+/// * `options` - Parameters loaded so far, from which the grid size, the resolution, and the rotation are used.
+fn load_view(args: &[String], options: &Options) -> Result<(Option<Coord<f64>>, f64), Error> {
+    const KEYS_CENTER: Coord<&str> = Coord {
+        x: "center_x",
+        y: "center_y",
+    };
+    const KEYS_RECT: [&str; 4] = ["xmin", "xmax", "ymin", "ymax"];
+    const KEY_ZOOM: &str = "zoom_rect";
+    let mut grid_size: f64 = options.grid_size;
+    // center
+    let mut values: [Option<f64>; 2] = [None; 2];
+    for (m, key) in [KEYS_CENTER.x, KEYS_CENTER.y].iter().enumerate() {
+        values[m] = match extract_optional_value::<f64>(key, args) {
+            Ok(value) => value,
+            Err(msg) => {
+                return Err(Error::Argument(format!("{}: {}", key, msg)));
+            }
+        };
+    }
+    let explicit: Option<Coord<f64>> = match values {
+        [Some(x), Some(y)] => Some(Coord { x, y }),
+        [None, None] => None,
+        _ => {
+            return Err(Error::Argument(format!(
+                "{}, {}: expect both to be given",
+                KEYS_CENTER.x, KEYS_CENTER.y
+            )));
+        }
+    };
+    // the center given explicitly takes precedence over the one given by a location
+    let mut center: Option<Coord<f64>> = explicit.or(options.center);
+    // rectangle
+    let mut values: [Option<f64>; 4] = [None; 4];
+    for (m, key) in KEYS_RECT.iter().enumerate() {
+        values[m] = match extract_optional_value::<f64>(key, args) {
+            Ok(value) => value,
+            Err(msg) => {
+                return Err(Error::Argument(format!("{}: {}", key, msg)));
+            }
+        };
+    }
+    match values {
+        [Some(xmin), Some(xmax), Some(ymin), Some(ymax)] => {
+            if explicit.is_some() {
+                return Err(Error::Argument(format!(
+                    "{}, {}: expect either center or rectangle",
+                    KEYS_CENTER.x, KEYS_RECT[0]
+                )));
+            }
+            if xmax <= xmin || ymax <= ymin {
+                return Err(Error::Argument(format!(
+                    "{}: expect min < max",
+                    KEYS_RECT.join(", ")
+                )));
+            }
+            let (rect_center, rect_grid_size): (Coord<f64>, f64) = fit_rect(
+                &options.resols,
+                [Coord { x: xmin, y: ymin }, Coord { x: xmax, y: ymax }],
+            );
+            center = Some(rect_center);
+            grid_size = rect_grid_size;
+        }
+        [None, None, None, None] => {}
+        _ => {
+            return Err(Error::Argument(format!(
+                "{}: expect all to be given",
+                KEYS_RECT.join(", ")
+            )));
+        }
+    };
+    // zoom into a pixel rectangle of the view
+    let rect: Option<String> = match extract_optional_value::<String>(KEY_ZOOM, args) {
+        Ok(value) => value,
+        Err(msg) => {
+            return Err(Error::Argument(format!("{}: {}", KEY_ZOOM, msg)));
+        }
+    };
+    if let Some(rect) = rect {
+        let view: Coord<f64> = match center {
+            Some(view) => view,
+            None => {
+                return Err(Error::Argument(format!(
+                    "{}: expect center or rectangle to be given",
+                    KEY_ZOOM
+                )));
+            }
+        };
+        let pixels: Vec<f64> = match parse_list::<f64>(&rect, 4) {
+            Ok(pixels) => pixels,
+            Err(msg) => {
+                return Err(Error::Argument(format!("{}: {}", KEY_ZOOM, msg)));
+            }
+        };
+        if pixels[0] == pixels[2] || pixels[1] == pixels[3] {
+            return Err(Error::Argument(format!(
+                "{}: expect non-empty rectangle",
+                KEY_ZOOM
+            )));
+        }
+        let (rect_center, rect_grid_size): (Coord<f64>, f64) = zoom_rect(
+            &options.resols,
+            &view,
+            grid_size,
+            options.rotation,
+            [
+                Coord {
+                    x: pixels[0],
+                    y: pixels[1],
+                },
+                Coord {
+                    x: pixels[2],
+                    y: pixels[3],
+                },
+            ],
+        );
+        center = Some(rect_center);
+        grid_size = rect_grid_size;
+    }
+    return Ok((center, grid_size));
+}
+
+/// Computes the center and the grid size of the smallest view which contains the given rectangle.
+///   
+/// * `resols`  - Number of pixels in the two dimensions.
+/// * `corners` - Lower-left and upper-right corners of the rectangle in the complex plane.
+fn fit_rect(resols: &Coord<usize>, corners: [Coord<f64>; 2]) -> (Coord<f64>, f64) {
+    let center: Coord<f64> = Coord {
+        x: 0.5 * corners[0].x + 0.5 * corners[1].x,
+        y: 0.5 * corners[0].y + 0.5 * corners[1].y,
+    };
+    let grid_size: f64 = f64::max(
+        (corners[1].x - corners[0].x) / resols.x as f64,
+        (corners[1].y - corners[0].y) / resols.y as f64,
+    );
+    return (center, grid_size);
+}
+
+/// Computes the center and the grid size of the view which contains the given rectangle in pixels.
+///   
+/// * `resols`    - Number of pixels in the two dimensions.
+/// * `center`    - Center of the current view.
+/// * `grid_size` - Inter-pixel distance of the current view.
+/// * `rotation`  - Rotation angle of the current view.
+/// * `pixels`    - Two opposite corners of the rectangle in pixels.
+fn zoom_rect(
+    resols: &Coord<usize>,
+    center: &Coord<f64>,
+    grid_size: f64,
+    rotation: f64,
+    pixels: [Coord<f64>; 2],
+) -> (Coord<f64>, f64) {
+    // offset of the rectangle center from the view center
+    let offset: Coord<f64> = Coord {
+        x: (0.5 * pixels[0].x + 0.5 * pixels[1].x - 0.5 * resols.x as f64) * grid_size,
+        y: (0.5 * pixels[0].y + 0.5 * pixels[1].y - 0.5 * resols.y as f64) * grid_size,
+    };
+    let (sin, cos): (f64, f64) = rotation.sin_cos();
+    let center: Coord<f64> = Coord {
+        x: center.x + cos * offset.x - sin * offset.y,
+        y: center.y + sin * offset.x + cos * offset.y,
+    };
+    let grid_size: f64 = grid_size
+        * f64::max(
+            (pixels[1].x - pixels[0].x).abs() / resols.x as f64,
+            (pixels[1].y - pixels[0].y).abs() / resols.y as f64,
+        );
+    return (center, grid_size);
+}
+
+/// Loads a strategy to find a nice image center.
+///   
+/// * `args`    - All command-line arguments as a vector of strings.
+/// * `default` - Default value filled in the absence of the user specification.
+fn load_search(args: &[String], default: Search) -> Result<Search, Error> {
+    const KEY: &str = "search";
+    let value: Search = match extract_value::<Search>(KEY, args, default) {
+        Ok(value) => value,
+        Err(msg) => {
+            return Err(Error::Argument(format!("{}: {}", KEY, msg)));
+        }
+    };
+    return Ok(value);
+}
+
+/// Loads the region from which the search starts and try to interpret it as four `f64` values.
+///   
+/// * `args`    - All command-line arguments as a vector of strings.
+/// * `default` - Default value filled in the absence of the user specification.
+fn load_region(args: &[String], default: [Coord<f64>; 2]) -> Result<[Coord<f64>; 2], Error> {
+    const KEY: &str = "region";
+    let value: Option<String> = match extract_optional_value::<String>(KEY, args) {
+        Ok(value) => value,
+        Err(msg) => {
+            return Err(Error::Argument(format!("{}: {}", KEY, msg)));
+        }
+    };
+    let value: String = match value {
+        Some(value) => value,
+        None => return Ok(default),
+    };
+    let bounds: Vec<f64> = match parse_list::<f64>(&value, 4) {
+        Ok(bounds) => bounds,
+        Err(msg) => {
+            return Err(Error::Argument(format!("{}: {}", KEY, msg)));
+        }
+    };
+    // check being non-empty
+    if bounds[0] < bounds[1] && bounds[2] < bounds[3] {
+        return Ok([
+            Coord {
+                x: bounds[0],
+                y: bounds[2],
+            },
+            Coord {
+                x: bounds[1],
+                y: bounds[3],
+            },
+        ]);
+    } else {
+        return Err(Error::Argument(format!(
+            "{}: expect xmin < xmax and ymin < ymax",
+            KEY
+        )));
+    }
+}
+
+/// Loads a strategy to draw the initial center of the search.
+///   
+/// * `args`    - All command-line arguments as a vector of strings.
+/// * `default` - Default value filled in the absence of the user specification.
+fn load_sampling(args: &[String], default: Sampling) -> Result<Sampling, Error> {
+    const KEY: &str = "sampling";
+    let value: Sampling = match extract_value::<Sampling>(KEY, args, default) {
+        Ok(value) => value,
+        Err(msg) => {
+            return Err(Error::Argument(format!("{}: {}", KEY, msg)));
+        }
+    };
+    return Ok(value);
+}
+
+/// Loads a flag to confine the search from the command-line arguments and try to interpret it as a `bool` value.
+///   
+/// * `args`    - All command-line arguments as a vector of strings.
+/// * `default` - Default value filled in the absence of the user specification.
+fn load_confine(args: &[String], default: bool) -> Result<bool, Error> {
+    const KEY: &str = "confine";
+    let value: bool = match extract_value::<bool>(KEY, args, default) {
+        Ok(value) => value,
+        Err(msg) => {
+            return Err(Error::Argument(format!("{}: {}", KEY, msg)));
+        }
+    };
+    return Ok(value);
+}
+
+/// Loads the range of the zoom which the search reaches and try to interpret them as `f64` values.
+///   
+/// * `args` - All command-line arguments as a vector of strings.
+fn load_zoom_range(args: &[String]) -> Result<(Option<f64>, Option<f64>), Error> {
+    const KEYS: [&str; 2] = ["min_zoom", "max_zoom"];
+    let mut values: [Option<f64>; 2] = [None; 2];
+    for (m, key) in KEYS.iter().enumerate() {
+        values[m] = match extract_optional_value::<f64>(key, args) {
+            Ok(value) => value,
+            Err(msg) => {
+                return Err(Error::Argument(format!("{}: {}", key, msg)));
+            }
+        };
+        // check being positive
+        if let Some(value) = values[m] {
+            if value <= 0. {
+                return Err(Error::Argument(format!("{}: expect positive number", key)));
+            }
+        }
+    }
+    if let [Some(min), Some(max)] = values {
+        if max < min {
+            return Err(Error::Argument(format!(
+                "{}, {}: expect {} <= {}",
+                KEYS[0], KEYS[1], KEYS[0], KEYS[1]
+            )));
+        }
+    }
+    return Ok((values[0], values[1]));
+}
+
+/// Loads the thresholds of the quality scores and try to interpret them as `f64` values.
+///   
+/// The thresholds are given in the order of
+/// the maximum interior fraction, the minimum colour variance, the minimum edge density, and the minimum histogram spread.
+///   
+/// * `args`    - All command-line arguments as a vector of strings.
+/// * `default` - Default values filled in the absence of the user specification.
+fn load_thresholds(args: &[String], default: [f64; 4]) -> Result<[f64; 4], Error> {
+    const KEYS: [&str; 4] = [
+        "max_interior",
+        "min_colour_variance",
+        "min_edge_density",
+        "min_histogram_spread",
+    ];
+    let mut values: [f64; 4] = default;
+    for (m, key) in KEYS.iter().enumerate() {
+        values[m] = match extract_value::<f64>(key, args, default[m]) {
+            Ok(value) => value,
+            Err(msg) => {
+                return Err(Error::Argument(format!("{}: {}", key, msg)));
+            }
+        };
+        // all scores are in [0:1]
+        if !(0. ..=1.).contains(&values[m]) {
+            return Err(Error::Argument(format!("{}: expect number in [0:1]", key)));
+        }
+    }
+    return Ok(values);
+}
+
+/// Loads the number of retries of the search from the command-line arguments and try to interpret it as a `usize` value.
+///   
+/// * `args`    - All command-line arguments as a vector of strings.
+/// * `default` - Default value filled in the absence of the user specification.
+fn load_retries(args: &[String], default: usize) -> Result<usize, Error> {
+    const KEY: &str = "retries";
+    let value: usize = match extract_value::<usize>(KEY, args, default) {
+        Ok(value) => value,
+        Err(msg) => {
+            return Err(Error::Argument(format!("{}: {}", KEY, msg)));
+        }
+    };
+    return Ok(value);
+}
+
+/// Loads the name of a palette from the command-line arguments,
+/// which is checked to be a built-in one or a readable palette file.
+///   
+/// * `args`    - All command-line arguments as a vector of strings.
+/// * `default` - Default value filled in the absence of the user specification.
+fn load_palette(args: &[String], default: String) -> Result<String, Error> {
+    const KEY: &str = "palette";
+    let value: String = match extract_value::<String>(KEY, args, default) {
+        Ok(value) => value,
+        Err(msg) => {
+            return Err(Error::Argument(format!("{}: {}", KEY, msg)));
+        }
+    };
+    return match palette::load(&value) {
+        Ok(_) => Ok(value),
+        Err(error) => Err(error),
+    };
+}
+
+/// Loads the treatment of the positions outside a gradient palette from the command-line arguments.
+///   
+/// * `args`    - All command-line arguments as a vector of strings.
+/// * `default` - Default value filled in the absence of the user specification.
+fn load_palette_mode(args: &[String], default: PaletteMode) -> Result<PaletteMode, Error> {
+    const KEY: &str = "palette_mode";
+    let value: PaletteMode = match extract_value::<PaletteMode>(KEY, args, default) {
+        Ok(value) => value,
+        Err(msg) => {
+            return Err(Error::Argument(format!("{}: {}", KEY, msg)));
+        }
+    };
+    return Ok(value);
+}
+
+/// Loads the number of repeats of the colour palette from the command-line arguments and try to interpret it as a `f64` value.
+///   
+/// * `args`    - All command-line arguments as a vector of strings.
+/// * `default` - Default value filled in the absence of the user specification.
+fn load_palette_repeat(args: &[String], default: f64) -> Result<f64, Error> {
+    const KEY: &str = "palette_repeat";
+    let value: f64 = match extract_value::<f64>(KEY, args, default) {
+        Ok(value) => value,
+        Err(msg) => {
+            return Err(Error::Argument(format!("{}: {}", KEY, msg)));
+        }
+    };
+    // check being positive
+    if value > 0. {
+        return Ok(value);
+    } else {
+        return Err(Error::Argument(format!("{}: expect positive number", KEY)));
+    }
+}
+
+/// Loads the dithering method from the command-line arguments.
+///   
+/// * `args`    - All command-line arguments as a vector of strings.
+/// * `default` - Default value filled in the absence of the user specification.
+fn load_dither(args: &[String], default: Dither) -> Result<Dither, Error> {
+    const KEY: &str = "dither";
+    let value: Dither = match extract_value::<Dither>(KEY, args, default) {
+        Ok(value) => value,
+        Err(msg) => {
+            return Err(Error::Argument(format!("{}: {}", KEY, msg)));
+        }
+    };
+    return Ok(value);
+}
+
+/// Loads the colour space of the interpolation from the command-line arguments.
+///   
+/// * `args`    - All command-line arguments as a vector of strings.
+/// * `default` - Default value filled in the absence of the user specification.
+fn load_colour_space(args: &[String], default: ColourSpace) -> Result<ColourSpace, Error> {
+    const KEY: &str = "colour_space";
+    let value: ColourSpace = match extract_value::<ColourSpace>(KEY, args, default) {
+        Ok(value) => value,
+        Err(msg) => {
+            return Err(Error::Argument(format!("{}: {}", KEY, msg)));
+        }
     };
-    // load parameters
-    let args: Vec<String> = env::args().collect();
-    // update default parameters if exist
-    options.seed = match load_seed(&args, options.seed) {
-        Ok(seed) => seed,
-        Err(_) => {
-            print_error_message();
-            return Err(());
+    return Ok(value);
+}
+
+/// Loads the colour vision deficiency to be simulated from the command-line arguments.
+///   
+/// * `args`    - All command-line arguments as a vector of strings.
+/// * `default` - Default value filled in the absence of the user specification.
+fn load_simulate(
+    args: &[String],
+    default: Option<Deficiency>,
+) -> Result<Option<Deficiency>, Error> {
+    const KEY: &str = "simulate";
+    let value: Option<Deficiency> = match extract_optional_value::<Deficiency>(KEY, args) {
+        Ok(value) => value,
+        Err(msg) => {
+            return Err(Error::Argument(format!("{}: {}", KEY, msg)));
         }
     };
-    options.grid_size = match load_grid_size(&args, options.grid_size) {
-        Ok(grid_size) => grid_size,
-        Err(_) => {
-            print_error_message();
-            return Err(());
+    return Ok(value.or(default));
+}
+
+/// Loads the transfer function from the command-line arguments.
+///   
+/// * `args`    - All command-line arguments as a vector of strings.
+/// * `default` - Default value filled in the absence of the user specification.
+fn load_transfer(args: &[String], default: Transfer) -> Result<Transfer, Error> {
+    const KEY: &str = "transfer";
+    let value: Transfer = match extract_value::<Transfer>(KEY, args, default) {
+        Ok(value) => value,
+        Err(msg) => {
+            return Err(Error::Argument(format!("{}: {}", KEY, msg)));
         }
     };
-    options.resols = match load_resols(&args, options.resols) {
-        Ok(resols) => resols,
-        Err(_) => {
-            print_error_message();
-            return Err(());
+    return Ok(value);
+}
+
+/// Loads the exponent of the power transfer function from the command-line arguments and try to interpret it as a `f64` value.
+///   
+/// * `args`    - All command-line arguments as a vector of strings.
+/// * `default` - Default value filled in the absence of the user specification.
+fn load_transfer_exponent(args: &[String], default: f64) -> Result<f64, Error> {
+    const KEY: &str = "transfer_exponent";
+    let value: f64 = match extract_value::<f64>(KEY, args, default) {
+        Ok(value) => value,
+        Err(msg) => {
+            return Err(Error::Argument(format!("{}: {}", KEY, msg)));
         }
     };
-    options.fname = match load_fname(&args, options.fname) {
-        Ok(fname) => fname,
-        Err(_) => {
-            print_error_message();
-            return Err(());
+    // check being positive
+    if value > 0. {
+        return Ok(value);
+    } else {
+        return Err(Error::Argument(format!("{}: expect positive number", KEY)));
+    }
+}
+
+/// Loads the steepness of the arctangent transfer function from the command-line arguments and try to interpret it as a `f64` value.
+///   
+/// * `args`    - All command-line arguments as a vector of strings.
+/// * `default` - Default value filled in the absence of the user specification.
+fn load_transfer_steepness(args: &[String], default: f64) -> Result<f64, Error> {
+    const KEY: &str = "transfer_steepness";
+    let value: f64 = match extract_value::<f64>(KEY, args, default) {
+        Ok(value) => value,
+        Err(msg) => {
+            return Err(Error::Argument(format!("{}: {}", KEY, msg)));
         }
     };
-    print_last_message(&options);
-    return Ok(options);
+    // check being positive
+    if value > 0. {
+        return Ok(value);
+    } else {
+        return Err(Error::Argument(format!("{}: expect positive number", KEY)));
+    }
 }
 
-/// Outputs an error message before abort.
-fn print_error_message() -> () {
-    println!("ERROR: failed to load command-line arguments.");
-    println!("USAGE: cargo run -- --<key>=<value>");
-    println!("Available keys and their types / requirements are listed below.");
-    println!("    seed      : positive integer (u64)");
-    println!("    grid_size : positive floating number (f64)");
-    println!("    width     : positive integer number (usize)");
-    println!("    height    : positive integer number (usize)");
-    println!("    fname     : string which ends with 'ppm'");
-    println!("See also README.rst.");
+/// Loads the percentiles clipping the number of iterations from the command-line arguments.
+///   
+/// * `args`    - All command-line arguments as a vector of strings.
+/// * `default` - Default value filled in the absence of the user specification.
+fn load_clip(args: &[String], default: Option<[f64; 2]>) -> Result<Option<[f64; 2]>, Error> {
+    const KEY: &str = "clip";
+    let value: String = match extract_optional_value::<String>(KEY, args) {
+        Ok(Some(value)) => value,
+        Ok(None) => return Ok(default),
+        Err(msg) => {
+            return Err(Error::Argument(format!("{}: {}", KEY, msg)));
+        }
+    };
+    let bounds: Vec<f64> = match parse_list::<f64>(&value, 2) {
+        Ok(bounds) => bounds,
+        Err(msg) => {
+            return Err(Error::Argument(format!("{}: {}", KEY, msg)));
+        }
+    };
+    // check being a non-empty range of percentiles
+    if 0. <= bounds[0] && bounds[0] < bounds[1] && bounds[1] <= 100. {
+        return Ok(Some([bounds[0], bounds[1]]));
+    } else {
+        return Err(Error::Argument(format!(
+            "{}: expect 0 <= lower < upper <= 100",
+            KEY
+        )));
+    }
 }
 
-/// Outputs an log message before return.
+/// Loads the height field of the relief from the command-line arguments.
 ///   
-/// * `options` - Parameters loaded and / or assigned.
-fn print_last_message(options: &Options) -> () {
-    println!("The settings are configured as follows.");
-    println!("    random seed     : {}", options.seed);
-    println!("    grid size       : {}", options.grid_size);
-    println!("    width           : {}", options.resols.x);
-    println!("    height          : {}", options.resols.y);
-    println!("    image file name : {}", options.fname);
+/// * `args`    - All command-line arguments as a vector of strings.
+/// * `default` - Default value filled in the absence of the user specification.
+fn load_relief(args: &[String], default: Option<Relief>) -> Result<Option<Relief>, Error> {
+    const KEY: &str = "relief";
+    let value: Option<Relief> = match extract_optional_value::<Relief>(KEY, args) {
+        Ok(value) => value,
+        Err(msg) => {
+            return Err(Error::Argument(format!("{}: {}", KEY, msg)));
+        }
+    };
+    return Ok(value.or(default));
 }
 
-/// Loads a random seed from the command-line arguments and try to interpret it as a `u64` value.
+/// Loads the direction of the light from the command-line arguments and try to interpret it as a `f64` value.
 ///   
 /// * `args`    - All command-line arguments as a vector of strings.
 /// * `default` - Default value filled in the absence of the user specification.
-fn load_seed(args: &Vec<String>, default: u64) -> Result<u64, ()> {
-    const KEY: &str = "seed";
-    let value: u64 = match extract_value::<u64>(KEY, args, default) {
+fn load_light_azimuth(args: &[String], default: f64) -> Result<f64, Error> {
+    const KEY: &str = "light_azimuth";
+    let value: f64 = match extract_value::<f64>(KEY, args, default) {
         Ok(value) => value,
         Err(msg) => {
-            println!("{}: {}", KEY, msg);
-            return Err(());
+            return Err(Error::Argument(format!("{}: {}", KEY, msg)));
         }
     };
     return Ok(value);
 }
 
-/// Loads a grid size from the command-line arguments and try to interpret it as a `f64` value.
+/// Loads the elevation of the light from the command-line arguments and try to interpret it as a `f64` value.
 ///   
 /// * `args`    - All command-line arguments as a vector of strings.
 /// * `default` - Default value filled in the absence of the user specification.
-fn load_grid_size(args: &Vec<String>, default: f64) -> Result<f64, ()> {
-    const KEY: &str = "grid_size";
+fn load_light_elevation(args: &[String], default: f64) -> Result<f64, Error> {
+    const KEY: &str = "light_elevation";
+    let value: f64 = match extract_value::<f64>(KEY, args, default) {
+        Ok(value) => value,
+        Err(msg) => {
+            return Err(Error::Argument(format!("{}: {}", KEY, msg)));
+        }
+    };
+    // check being above the image
+    if (0. ..=90.).contains(&value) {
+        return Ok(value);
+    } else {
+        return Err(Error::Argument(format!("{}: expect number in [0:90]", KEY)));
+    }
+}
+
+/// Loads the ambient brightness of the relief from the command-line arguments and try to interpret it as a `f64` value.
+///   
+/// * `args`    - All command-line arguments as a vector of strings.
+/// * `default` - Default value filled in the absence of the user specification.
+fn load_ambient(args: &[String], default: f64) -> Result<f64, Error> {
+    const KEY: &str = "ambient";
+    let value: f64 = match extract_value::<f64>(KEY, args, default) {
+        Ok(value) => value,
+        Err(msg) => {
+            return Err(Error::Argument(format!("{}: {}", KEY, msg)));
+        }
+    };
+    // check being a fraction
+    if (0. ..=1.).contains(&value) {
+        return Ok(value);
+    } else {
+        return Err(Error::Argument(format!("{}: expect number in [0:1]", KEY)));
+    }
+}
+
+/// Loads the intensity of the specular highlights from the command-line arguments and try to interpret it as a `f64` value.
+///   
+/// * `args`    - All command-line arguments as a vector of strings.
+/// * `default` - Default value filled in the absence of the user specification.
+fn load_specular(args: &[String], default: f64) -> Result<f64, Error> {
+    const KEY: &str = "specular";
+    let value: f64 = match extract_value::<f64>(KEY, args, default) {
+        Ok(value) => value,
+        Err(msg) => {
+            return Err(Error::Argument(format!("{}: {}", KEY, msg)));
+        }
+    };
+    // check being non-negative
+    if value >= 0. {
+        return Ok(value);
+    } else {
+        return Err(Error::Argument(format!(
+            "{}: expect non-negative number",
+            KEY
+        )));
+    }
+}
+
+/// Loads the factor exaggerating the relief from the command-line arguments and try to interpret it as a `f64` value.
+///   
+/// * `args`    - All command-line arguments as a vector of strings.
+/// * `default` - Default value filled in the absence of the user specification.
+fn load_relief_strength(args: &[String], default: f64) -> Result<f64, Error> {
+    const KEY: &str = "relief_strength";
     let value: f64 = match extract_value::<f64>(KEY, args, default) {
         Ok(value) => value,
         Err(msg) => {
-            println!("{}: {}", KEY, msg);
-            return Err(());
+            return Err(Error::Argument(format!("{}: {}", KEY, msg)));
         }
     };
     // check being positive
     if value > 0. {
         return Ok(value);
     } else {
-        println!("{}: expect positive number", KEY);
-        return Err(());
+        return Err(Error::Argument(format!("{}: expect positive number", KEY)));
     }
 }
 
-/// Loads `width` and `height` from the command-line arguments and try to interpret them as `usize` values.
+/// Loads the weight of the shaded colours from the command-line arguments and try to interpret it as a `f64` value.
 ///   
 /// * `args`    - All command-line arguments as a vector of strings.
 /// * `default` - Default value filled in the absence of the user specification.
-fn load_resols(args: &Vec<String>, default: Coord<usize>) -> Result<Coord<usize>, ()> {
-    const KEYS: Coord<&str> = Coord {
-        x: "width",
-        y: "height",
+fn load_relief_blend(args: &[String], default: f64) -> Result<f64, Error> {
+    const KEY: &str = "relief_blend";
+    let value: f64 = match extract_value::<f64>(KEY, args, default) {
+        Ok(value) => value,
+        Err(msg) => {
+            return Err(Error::Argument(format!("{}: {}", KEY, msg)));
+        }
     };
-    let resols: Coord<usize> = Coord {
-        x: match extract_value::<usize>(KEYS.x, args, default.x) {
-            Ok(value) => value,
-            Err(msg) => {
-                println!("{}: {}", KEYS.x, msg);
-                return Err(());
-            }
-        },
-        y: match extract_value::<usize>(KEYS.y, args, default.y) {
-            Ok(value) => value,
-            Err(msg) => {
-                println!("{}: {}", KEYS.y, msg);
-                return Err(());
-            }
-        },
+    // check being a fraction
+    if (0. ..=1.).contains(&value) {
+        return Ok(value);
+    } else {
+        return Err(Error::Argument(format!("{}: expect number in [0:1]", KEY)));
+    }
+}
+
+/// Loads a phase shift of the colour palette from the command-line arguments and try to interpret it as a `f64` value.
+///   
+/// * `args`    - All command-line arguments as a vector of strings.
+/// * `default` - Default value filled in the absence of the user specification.
+fn load_palette_offset(args: &[String], default: f64) -> Result<f64, Error> {
+    const KEY: &str = "palette_offset";
+    let value: f64 = match extract_value::<f64>(KEY, args, default) {
+        Ok(value) => value,
+        Err(msg) => {
+            return Err(Error::Argument(format!("{}: {}", KEY, msg)));
+        }
     };
-    return Ok(resols);
+    return Ok(value);
 }
 
-/// Load the name of the final image from the command-line arguments and validate its suffix.
+/// Loads the name of a keyframe file from the command-line arguments.
 ///   
 /// * `args`    - All command-line arguments as a vector of strings.
 /// * `default` - Default value filled in the absence of the user specification.
-fn load_fname(args: &Vec<String>, default: String) -> Result<String, ()> {
-    const KEY: &str = "fname";
-    let value: String = match extract_value::<String>(KEY, args, default) {
+fn load_keyframes(args: &[String], default: Option<String>) -> Result<Option<String>, Error> {
+    const KEY: &str = "keyframes";
+    let value: Option<String> = match extract_optional_value::<String>(KEY, args) {
         Ok(value) => value,
         Err(msg) => {
-            println!("{}: {}", KEY, msg);
-            return Err(());
+            return Err(Error::Argument(format!("{}: {}", KEY, msg)));
         }
     };
-    // check suffix
-    if value.ends_with(".ppm") {
+    return Ok(value.or(default));
+}
+
+/// Loads the number of frames per unit time from the command-line arguments and try to interpret it as a `f64` value.
+///   
+/// * `args`    - All command-line arguments as a vector of strings.
+/// * `default` - Default value filled in the absence of the user specification.
+fn load_fps(args: &[String], default: f64) -> Result<f64, Error> {
+    const KEY: &str = "fps";
+    let value: f64 = match extract_value::<f64>(KEY, args, default) {
+        Ok(value) => value,
+        Err(msg) => {
+            return Err(Error::Argument(format!("{}: {}", KEY, msg)));
+        }
+    };
+    // check being positive
+    if value > 0. {
+        return Ok(value);
+    } else {
+        return Err(Error::Argument(format!("{}: expect positive number", KEY)));
+    }
+}
+
+/// Loads the number of threads from the command-line arguments and try to interpret it as a `usize` value.
+///   
+/// * `args`    - All command-line arguments as a vector of strings.
+/// * `default` - Default value filled in the absence of the user specification.
+fn load_threads(args: &[String], default: usize) -> Result<usize, Error> {
+    const KEY: &str = "threads";
+    let value: usize = match extract_value::<usize>(KEY, args, default) {
+        Ok(value) => value,
+        Err(msg) => {
+            return Err(Error::Argument(format!("{}: {}", KEY, msg)));
+        }
+    };
+    // check being positive
+    if value > 0 {
         return Ok(value);
     } else {
-        println!("{}: expect suffix \".ppm\"", KEY);
-        return Err(());
+        return Err(Error::Argument(format!("{}: expect positive number", KEY)));
     }
 }
 
+/// Loads the index of the first frame to be rendered from the command-line arguments and try to interpret it as a `usize` value.
+///   
+/// * `args`    - All command-line arguments as a vector of strings.
+/// * `default` - Default value filled in the absence of the user specification.
+fn load_resume_from(args: &[String], default: usize) -> Result<usize, Error> {
+    const KEY: &str = "resume_from";
+    let value: usize = match extract_value::<usize>(KEY, args, default) {
+        Ok(value) => value,
+        Err(msg) => {
+            return Err(Error::Argument(format!("{}: {}", KEY, msg)));
+        }
+    };
+    return Ok(value);
+}
+
+/// Loads the resolutions of the images rendered at the same center from the command-line arguments.
+///   
+/// * `args`    - All command-line arguments as a vector of strings.
+/// * `default` - Default value filled in the absence of the user specification.
+fn load_sizes(args: &[String], default: Vec<Coord<usize>>) -> Result<Vec<Coord<usize>>, Error> {
+    const KEY: &str = "sizes";
+    let value: Option<String> = match extract_optional_value::<String>(KEY, args) {
+        Ok(value) => value,
+        Err(msg) => {
+            return Err(Error::Argument(format!("{}: {}", KEY, msg)));
+        }
+    };
+    return match value {
+        Some(value) => match parse_sizes(&value) {
+            Ok(sizes) => Ok(sizes),
+            Err(msg) => Err(Error::Argument(format!("{}: {}", KEY, msg))),
+        },
+        None => Ok(default),
+    };
+}
+
+/// Loads the quantity kept among the sizes from the command-line arguments.
+///   
+/// * `args`    - All command-line arguments as a vector of strings.
+/// * `default` - Default value filled in the absence of the user specification.
+fn load_size_scale(args: &[String], default: SizeScale) -> Result<SizeScale, Error> {
+    const KEY: &str = "size_scale";
+    let value: SizeScale = match extract_value::<SizeScale>(KEY, args, default) {
+        Ok(value) => value,
+        Err(msg) => {
+            return Err(Error::Argument(format!("{}: {}", KEY, msg)));
+        }
+    };
+    return Ok(value);
+}
+
+/// Loads the name of a layout file from the command-line arguments.
+///   
+/// * `args`    - All command-line arguments as a vector of strings.
+/// * `default` - Default value filled in the absence of the user specification.
+fn load_layout(args: &[String], default: Option<String>) -> Result<Option<String>, Error> {
+    const KEY: &str = "layout";
+    let value: Option<String> = match extract_optional_value::<String>(KEY, args) {
+        Ok(value) => value,
+        Err(msg) => {
+            return Err(Error::Argument(format!("{}: {}", KEY, msg)));
+        }
+    };
+    return Ok(value.or(default));
+}
+
+/// Splits a comma-separated list of resolutions, each of which is given as `<width>x<height>`.
+///   
+/// * `value` - "widthxheight,widthxheight,...".
+fn parse_sizes(value: &str) -> Result<Vec<Coord<usize>>, &'static str> {
+    const MSG: &str = "expect positive resolutions like 1280x800";
+    let mut sizes: Vec<Coord<usize>> = Vec::new();
+    for item in value.split(',') {
+        let (x, y): (&str, &str) = match item.trim().split_once('x') {
+            Some(pair) => pair,
+            None => return Err(MSG),
+        };
+        let size: Coord<usize> = match (x.parse::<usize>(), y.parse::<usize>()) {
+            (Ok(x), Ok(y)) if 0 < x && 0 < y => Coord { x, y },
+            _ => return Err(MSG),
+        };
+        sizes.push(size);
+    }
+    return Ok(sizes);
+}
+
 /// Kernel function used to extract a value attached with the given key and convert it to the desired data type.
 ///   
 /// * `key`     - Key whose attached value will be checked.
@@ -189,7 +2442,7 @@ fn load_fname(args: &Vec<String>, default: String) -> Result<String, ()> {
 /// * `default` - Default value filled in the absence of the user specification.
 fn extract_value<T: std::str::FromStr>(
     key: &str,
-    args: &Vec<String>,
+    args: &[String],
     default: T,
 ) -> Result<T, &'static str> {
     // from --<key>=<value>, extract value
@@ -204,22 +2457,62 @@ fn extract_value<T: std::str::FromStr>(
     return Ok(default);
 }
 
+/// Kernel function used to extract an optional value attached with the given key and convert it to the desired data type.
+///   
+/// * `key`     - Key whose attached value will be checked.
+/// * `args`    - All command-line arguments as a vector of strings.
+fn extract_optional_value<T: std::str::FromStr>(
+    key: &str,
+    args: &[String],
+) -> Result<Option<T>, &'static str> {
+    // from --<key>=<value>, extract value
+    let header: String = format!("--{}=", key);
+    for arg in args.iter() {
+        if !arg.starts_with(&header) {
+            continue;
+        }
+        return match parse_and_extract::<T>(&header, arg) {
+            Ok(value) => Ok(Some(value)),
+            Err(msg) => Err(msg),
+        };
+    }
+    // not found
+    return Ok(None);
+}
+
+/// Splits a comma-separated list and converts each item to the desired data type.
+///   
+/// * `value` - "item,item,...".
+/// * `nitems` - Expected number of items.
+fn parse_list<T: std::str::FromStr>(value: &str, nitems: usize) -> Result<Vec<T>, &'static str> {
+    const MSG1: &str = "unexpected number of items";
+    const MSG2: &str = "invalid value";
+    let mut items: Vec<T> = Vec::with_capacity(nitems);
+    for item in value.split(',') {
+        match item.trim().parse::<T>() {
+            Ok(item) => items.push(item),
+            Err(_) => return Err(MSG2),
+        };
+    }
+    if nitems != items.len() {
+        return Err(MSG1);
+    }
+    return Ok(items);
+}
+
 /// Extract the value by parsing a key-value pair.
 ///   
 /// * `header` - "--key=".
 /// * `arg`    - "--key=value".
-fn parse_and_extract<T: std::str::FromStr>(
-    header: &String,
-    arg: &String,
-) -> Result<T, &'static str> {
-    const MSG1: &'static str = "empty value";
-    const MSG2: &'static str = "invalid value";
+fn parse_and_extract<T: std::str::FromStr>(header: &String, arg: &str) -> Result<T, &'static str> {
+    const MSG1: &str = "empty value";
+    const MSG2: &str = "invalid value";
     // try to take the value out
     let value: &str = match arg.split(header).nth(1) {
         Some(value) => value,
         None => return Err(MSG1),
     };
-    if "" == value {
+    if value.is_empty() {
         return Err(MSG1);
     }
     // try to convert from &str to T
@@ -30,3 +30,22 @@ mod test_parse_and_extract_usize {
         );
     }
 }
+
+#[cfg(test)]
+mod test_parse_sizes {
+    use crate::coord::Coord;
+    use crate::options::parse_sizes;
+    #[test]
+    fn test0() -> () {
+        let sizes: Vec<Coord<usize>> = parse_sizes("1280x800, 3840x2160").unwrap();
+        assert_eq!(2, sizes.len());
+        assert_eq!((1280, 800), (sizes[0].x, sizes[0].y));
+        assert_eq!((3840, 2160), (sizes[1].x, sizes[1].y));
+    }
+    #[test]
+    fn test1() -> () {
+        for value in ["1280", "1280x", "0x800", "1280x800,", "1280X800"] {
+            assert!(parse_sizes(value).is_err());
+        }
+    }
+}
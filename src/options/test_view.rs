@@ -0,0 +1,93 @@
+#[cfg(test)]
+mod test_parse_list {
+    use crate::options::parse_list;
+    #[test]
+    fn test0() -> () {
+        assert_eq!(
+            Ok(vec![1., 2., 3.5, 4.]),
+            parse_list::<f64>("1,2, 3.5,4", 4)
+        );
+    }
+    #[test]
+    fn test1() -> () {
+        assert_eq!(
+            Err("unexpected number of items"),
+            parse_list::<f64>("1,2,3", 4)
+        );
+    }
+    #[test]
+    fn test2() -> () {
+        assert_eq!(Err("invalid value"), parse_list::<f64>("1,,3,4", 4));
+    }
+}
+
+#[cfg(test)]
+mod test_fit_rect {
+    use crate::coord::Coord;
+    use crate::options::fit_rect;
+    #[test]
+    fn test0() -> () {
+        let (center, grid_size): (Coord<f64>, f64) = fit_rect(
+            &Coord { x: 200, y: 100 },
+            [Coord { x: -2., y: -1. }, Coord { x: 0., y: 2. }],
+        );
+        assert_eq!(-1., center.x);
+        assert_eq!(0.5, center.y);
+        assert_eq!(0.03, grid_size);
+    }
+}
+
+#[cfg(test)]
+mod test_zoom_rect {
+    use crate::coord::Coord;
+    use crate::options::zoom_rect;
+    #[test]
+    fn test0() -> () {
+        let (center, grid_size): (Coord<f64>, f64) = zoom_rect(
+            &Coord { x: 200, y: 100 },
+            &Coord { x: 1., y: 1. },
+            0.01,
+            0.,
+            [Coord { x: 150., y: 50. }, Coord { x: 100., y: 100. }],
+        );
+        assert!((center.x - 1.25).abs() < 1e-12);
+        assert!((center.y - 1.25).abs() < 1e-12);
+        assert!((grid_size - 0.005).abs() < 1e-12);
+    }
+}
+
+#[cfg(test)]
+mod test_manifest_args {
+    use crate::manifest::Manifest;
+    use crate::options::manifest_args;
+    #[test]
+    fn test0() -> () {
+        let manifest: Manifest = Manifest::parse(
+            "fname = image.png\nformat = png\ndata = image.dat\ncenter_x = -0.5\ncenter_y = 0.1\nseed = 3\n",
+        )
+        .unwrap();
+        // the output files are never taken from the manifest
+        let records: Vec<String> = manifest_args(&[], &manifest).unwrap();
+        assert_eq!(
+            vec!["--center_x=-0.5", "--center_y=0.1", "--seed=3"],
+            records
+        );
+        // a rectangle replaces the recorded center
+        let args: Vec<String> = ["--xmin=-2", "--xmax=1", "--ymin=-1", "--ymax=1"]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect();
+        let records: Vec<String> = manifest_args(&args, &manifest).unwrap();
+        assert_eq!(vec!["--seed=3"], records);
+    }
+    #[test]
+    fn test1() -> () {
+        // the informative records are never fed back
+        let manifest: Manifest = Manifest::parse(
+            "version = 0.1.0\nseed = 3\nsizes = 640x400,320x200\nmax_iter = 1000\nfractal = mandelbrot\ntime_solve = 0.5\nquality_interior = 0.1\n",
+        )
+        .unwrap();
+        let records: Vec<String> = manifest_args(&[], &manifest).unwrap();
+        assert_eq!(vec!["--seed=3", "--sizes=640x400,320x200"], records);
+    }
+}
@@ -1,131 +1,415 @@
 #![deny(missing_docs)]
 
 //! Pixelises two-dimensional array and outputs the result as an image.
+//!
+//! Each image format is implemented as an [`ImageWriter`],
+//! which is chosen by [`crate::options::Format`].
+//! The image is written to the standard output when the file name is [`STDOUT`].
 
+mod bmp;
+mod dither;
+mod farbfeld;
+mod netpbm;
+mod pfm;
+mod png;
+mod qoi;
+mod relief;
+mod tga;
+mod transfer;
+mod zlib;
+
+use crate::atomic;
 use crate::coord::Coord;
+use crate::error::Error;
 use crate::mandelbrot::Point;
-use crate::options::Options;
+use crate::manifest::Manifest;
+use crate::options::{Format, Options};
+use crate::palette::{self, Palette};
+
+/// File name standing for the standard output.
+pub const STDOUT: &str = "-";
+
+/// Appends a suffix to the stem of an image file name, e.g., `dir.d/image.ppm` to `dir.d/image_00012.ppm`,
+/// for the jobs writing several images.
+///   
+/// * `fname`  - Name of the image file, which should not be [`STDOUT`].
+/// * `suffix` - Suffix telling each image apart.
+pub fn suffixed_fname(fname: &str, suffix: &str) -> Result<String, Error> {
+    if STDOUT == fname {
+        return Err(Error::Argument(String::from(
+            "fname: the standard output cannot take several images",
+        )));
+    }
+    let path: &std::path::Path = std::path::Path::new(fname);
+    let stem: String = match path.file_stem() {
+        Some(stem) => stem.to_string_lossy().into_owned(),
+        None => String::new(),
+    };
+    let name: String = match path.extension() {
+        Some(extension) => format!("{}_{}.{}", stem, suffix, extension.to_string_lossy()),
+        None => format!("{}_{}", stem, suffix),
+    };
+    return Ok(path.with_file_name(name).to_string_lossy().into_owned());
+}
+
+/// Describes the part of a larger view covered by an image, e.g., one of several screens,
+/// so that the parts are coloured consistently.
+pub struct Part<'a> {
+    /// lower and upper corners of the image, where the whole view is scaled to [0:1] in both directions
+    pub corners: [Coord<f64>; 2],
+    /// result of the recurrence relation over the whole view, by which the colours are normalised
+    pub whole: &'a [Point],
+}
+
+impl Part<'_> {
+    /// Gives the part covering the whole view.
+    ///
+    /// * `points` - Result of the recurrence relation for each point.
+    pub fn whole(points: &[Point]) -> Part<'_> {
+        return Part {
+            corners: [Coord { x: 0., y: 0. }, Coord { x: 1., y: 1. }],
+            whole: points,
+        };
+    }
+}
+
+/// Stores an image in a form from which every format can be encoded.
+///
+/// The colours are kept in floating-point numbers,
+/// which are quantised by each writer following its bit depth.
+pub struct Image {
+    /// number of pixels in two directions
+    pub resols: Coord<usize>,
+    /// number of bits per sample, 8 or 16, of the integer formats
+    pub depth: u8,
+    /// whether the alpha channel is requested
+    pub alpha: bool,
+    /// RGBA values in [0:1] of each pixel, row by row from the top, whose alpha is opaque unless requested
+    pub rgba: Vec<[f64; 4]>,
+    /// greyscale iteration map in [0:1] of each pixel
+    pub grey: Vec<f64>,
+    /// key-value records describing the render, embedded by the formats storing texts
+    pub metadata: Vec<(String, String)>,
+}
+
+impl Image {
+    /// Gives the maximum value of a sample.
+    pub fn max(&self) -> u16 {
+        return if 16 == self.depth { 65535 } else { 255 };
+    }
+
+    /// Gives the RGBA samples of a pixel in the bit depth.
+    ///
+    /// * `n` - Index of the pixel.
+    pub fn samples(&self, n: usize) -> [u16; 4] {
+        let max: f64 = self.max() as f64;
+        return self.rgba[n].map(|val| quantise(val, max));
+    }
+
+    /// Gives the greyscale sample of a pixel in the bit depth.
+    ///
+    /// * `n` - Index of the pixel.
+    pub fn grey_sample(&self, n: usize) -> u16 {
+        return quantise(self.grey[n], self.max() as f64);
+    }
+
+    /// Gives the RGBA samples of a pixel in 8 bits.
+    ///
+    /// * `n` - Index of the pixel.
+    pub fn rgba8(&self, n: usize) -> [u8; 4] {
+        return self.rgba[n].map(|val| quantise(val, 255.) as u8);
+    }
+
+    /// Gives the RGB samples of all pixels in 8 bits, row by row from the top.
+    pub fn rgb8(&self) -> Vec<u8> {
+        return (0..self.rgba.len())
+            .flat_map(|n| {
+                let rgba: [u8; 4] = self.rgba8(n);
+                [rgba[0], rgba[1], rgba[2]]
+            })
+            .collect();
+    }
+
+    /// Gives the RGBA samples of a pixel in 16 bits.
+    ///
+    /// * `n` - Index of the pixel.
+    pub fn rgba16(&self, n: usize) -> [u16; 4] {
+        return self.rgba[n].map(|val| quantise(val, 65535.));
+    }
+
+    /// Gives a small image with distinct samples to check the writers.
+    ///
+    /// * `depth` - Number of bits per sample.
+    /// * `alpha` - Whether the alpha channel is requested.
+    #[cfg(test)]
+    pub fn sample(depth: u8, alpha: bool) -> Image {
+        let resols: Coord<usize> = Coord { x: 3, y: 2 };
+        let nitems: usize = resols.x * resols.y;
+        // the last pixel is transparent when requested
+        let rgba: Vec<[f64; 4]> = (0..nitems)
+            .map(|n| {
+                let value = |m: usize| -> f64 { ((37 * (4 * n + m) + 11) % 256) as f64 / 255.3 };
+                let alpha: f64 = if alpha && nitems - 1 == n { 0. } else { 1. };
+                [value(0), value(1), value(2), alpha]
+            })
+            .collect();
+        return Image {
+            resols,
+            depth,
+            alpha,
+            rgba,
+            grey: (0..nitems).map(|n| n as f64 / 5.).collect(),
+            metadata: Vec::new(),
+        };
+    }
+}
+
+/// Encodes an image in a specific format.
+pub trait ImageWriter {
+    /// Converts the image to the bytes of a file,
+    /// or fails when the image cannot be stored in the format, e.g., because of its size.
+    ///
+    /// * `image` - Image to be encoded.
+    fn encode(&self, image: &Image) -> Result<Vec<u8>, Error>;
+}
+
+/// Gives the writer of the given format.
+///
+/// * `format` - Image format.
+pub fn writer(format: &Format) -> Box<dyn ImageWriter> {
+    return match format {
+        Format::Ppm => Box::new(netpbm::Ppm),
+        Format::Pgm => Box::new(netpbm::Pgm),
+        Format::Pam => Box::new(netpbm::Pam),
+        Format::Png => Box::new(png::Png),
+        Format::Bmp => Box::new(bmp::Bmp),
+        Format::Tga => Box::new(tga::Tga),
+        Format::Qoi => Box::new(qoi::Qoi),
+        Format::Farbfeld => Box::new(farbfeld::Farbfeld),
+        Format::Pfm => Box::new(pfm::Pfm),
+    };
+}
 
 /// Main function
 ///   
 /// * `options` - Fixed configuration parameters
+/// * `center`  - Center of the image
 /// * `point`   - Result of the recurrence relation for each point
-pub fn execute(options: &Options, points: Vec<Point>) -> Result<(), ()> {
-    let pixels: Vec<u8> = match pixelise(options, points) {
-        Ok(pixels) => pixels,
-        Err(_) => return Err(()),
-    };
-    let _ = match dump(options, pixels) {
+pub fn execute(options: &Options, center: &Coord<f64>, points: Vec<Point>) -> Result<(), Error> {
+    match dump(options, center, &points) {
         Ok(()) => return Ok(()),
-        Err(_) => return Err(()),
+        Err(error) => return Err(error),
     };
 }
 
 /// Converts a two-dimensional array telling the result of the
-/// recurrence relation to a set of pixels  
+/// recurrence relation to an image, which can be judged before written by [`write_image()`]
+///   
+/// * `options` - Fixed parameters: the number of pixels, the palette
+/// * `center`  - Center of the image
+/// * `points`  - Result of the recurrence relation for each point
+pub fn pixelise(options: &Options, center: &Coord<f64>, points: &[Point]) -> Result<Image, Error> {
+    return to_image(options, center, points, &Part::whole(points));
+}
+
+/// Converts the result of the recurrence relation to RGB values in [0:1]
 ///   
-/// * `options` - Fixed parameters: the number of pixels
+/// * `options` - Fixed parameters: the number of pixels, the palette
 /// * `point`   - Result of the recurrence relation for each point
-fn pixelise(options: &Options, points: Vec<Point>) -> Result<Vec<u8>, ()> {
+/// * `part`    - Part of the whole view covered by the image
+fn colourise(options: &Options, points: &[Point], part: &Part) -> Result<Vec<[f64; 3]>, Error> {
     let resols: &Coord<usize> = &options.resols;
     let nitems = resols.x * resols.y;
-    let mut pixels: Vec<u8> = vec![0u8; nitems * 3];
-    // find extrema
-    let min: u64 = match points.iter().min_by_key(|point| point.iter) {
-        Some(point) => point.iter,
-        None => {
-            println!("failed to find min");
-            return Err(());
-        }
-    };
-    let max: u64 = match points.iter().max_by_key(|point| point.iter) {
-        Some(point) => point.iter,
-        None => {
-            println!("failed to find max");
-            return Err(());
-        }
-    };
-    let min: f64 = min as f64;
-    let max: f64 = max as f64;
+    let mut colours: Vec<[f64; 3]> = vec![[0.; 3]; nitems];
+    let vals: Vec<f64> = transfer::normalise(options, points, part.whole)?;
+    let palette: Palette = palette::load(&options.palette)?;
     // convert
     for n in 0..nitems {
-        let is_diverged: bool = points[n].is_diverged;
-        let iter: f64 = points[n].iter as f64;
-        // enforce [0f64:1f64]
-        let val: f64 = if is_diverged {
-            (iter - min) / (max - min)
-        } else {
-            1.
-        };
-        // transform
-        let x: f64 = (n % resols.x) as f64 / resols.x as f64 - 0.5f64;
-        let y: f64 = (n / resols.x) as f64 / resols.y as f64 - 0.5f64;
-        let theta: f64 = y.atan2(x);
-        let val: [f64; 3] = to_rgb(theta, val);
-        // convert to u8
-        for m in 0..3 {
-            pixels[3 * n + m] = if val[m] > 1. {
-                255
-            } else if val[m] < 0. {
-                0
-            } else {
-                (255. * val[m]) as u8
-            };
+        // transform, relative to the center of the whole view
+        let [lower, upper] = &part.corners;
+        let x: f64 =
+            lower.x + (upper.x - lower.x) * (n % resols.x) as f64 / resols.x as f64 - 0.5f64;
+        let y: f64 =
+            lower.y + (upper.y - lower.y) * (n / resols.x) as f64 / resols.y as f64 - 0.5f64;
+        colours[n] = palette.colour(options, vals[n], y.atan2(x), points[n].is_diverged);
+    }
+    relief::shade(options, points, &mut colours);
+    // show how the image looks under the colour vision deficiency
+    if let Some(deficiency) = &options.simulate {
+        for colour in colours.iter_mut() {
+            *colour = palette::simulate(*colour, deficiency);
         }
     }
-    return Ok(pixels);
+    return Ok(colours);
+}
+
+/// Clips a value to [0:1] and rounds it to the nearest integer in [0:`max`]
+///   
+/// * `val` - Value of a sample
+/// * `max` - Maximum integer, 255 for 8 bits and 65535 for 16 bits
+fn quantise(val: f64, max: f64) -> u16 {
+    return (max * val.clamp(0., 1.)).round() as u16;
 }
 
 /// Outputs pixelised information as an image  
 ///   
+/// The format is given by `options.format`.
+/// The colours are computed again from the recurrence relation and kept in floating-point numbers
+/// until the writer quantises them, and the alpha channel makes the interior transparent.
+/// The seed, the view, the iteration limit, and the palette are embedded in the Netpbm and PNG formats.
+///   
 /// * `options` - Fixed parameters: output file name, the number of pixels
-/// * `pixels`  - Main data to be dumped
-fn dump(options: &Options, pixels: Vec<u8>) -> Result<(), ()> {
-    // I assume *.ppm
-    const MAGIC_NUMBER: &str = "P6";
-    // open and prepare stream
-    let file: std::fs::File = match std::fs::File::create(&options.fname) {
-        Ok(file) => file,
-        Err(_) => {
-            println!("failed to open file");
-            return Err(());
-        }
+/// * `center`  - Center of the image
+/// * `points`  - Result of the recurrence relation for each point
+pub fn dump(options: &Options, center: &Coord<f64>, points: &[Point]) -> Result<(), Error> {
+    return dump_part(options, center, points, &Part::whole(points));
+}
+
+/// Outputs an image which is a part of a larger view, e.g., one of several screens.
+///   
+/// See also: [`dump()`].
+///   
+/// * `options` - Fixed parameters: output file name, the number of pixels
+/// * `center`  - Center of the image
+/// * `points`  - Result of the recurrence relation for each point
+/// * `part`    - Part of the whole view covered by the image
+pub fn dump_part(
+    options: &Options,
+    center: &Coord<f64>,
+    points: &[Point],
+    part: &Part,
+) -> Result<(), Error> {
+    let image: Image = to_image(options, center, points, part)?;
+    return write_image(options, &image);
+}
+
+/// Encodes an image in the format and writes it.
+///   
+/// * `options` - Fixed parameters: output file name, the format
+/// * `image`   - Image to be written
+pub fn write_image(options: &Options, image: &Image) -> Result<(), Error> {
+    let bytes: Vec<u8> = writer(&options.format).encode(image)?;
+    return write(&options.fname, &bytes);
+}
+
+/// Writes the bytes of an image to a file atomically, or to the standard output when the file name is [`STDOUT`].
+///   
+/// * `fname` - Name of the image file.
+/// * `bytes` - Contents of the image file.
+fn write(fname: &str, bytes: &[u8]) -> Result<(), Error> {
+    use std::io::Write;
+    if STDOUT != fname {
+        return atomic::write(fname, bytes);
+    }
+    let mut stdout: std::io::StdoutLock = std::io::stdout().lock();
+    let result: std::io::Result<()> = stdout.write_all(bytes).and_then(|_| stdout.flush());
+    return match result {
+        Ok(_) => Ok(()),
+        Err(error) => Err(Error::Write(String::from(fname), error)),
     };
-    let mut stream: std::io::BufWriter<std::fs::File> = std::io::BufWriter::new(file);
-    // fwrite
-    let _size: usize = match std::io::Write::write(
-        &mut stream,
-        format!(
-            "{}\n{} {}\n255\n",
-            MAGIC_NUMBER, &options.resols.x, &options.resols.y
-        )
-        .as_bytes(),
-    ) {
-        Ok(size) => size,
-        Err(_) => {
-            println!("file write failed");
-            return Err(());
-        }
+}
+
+/// Collects the colours of each pixel, the alpha channel, and the metadata
+///   
+/// * `options` - Fixed parameters: bit depth and alpha channel
+/// * `center`  - Center of the image
+/// * `points`  - Result of the recurrence relation for each point
+/// * `part`    - Part of the whole view covered by the image
+fn to_image(
+    options: &Options,
+    center: &Coord<f64>,
+    points: &[Point],
+    part: &Part,
+) -> Result<Image, Error> {
+    let colours: Vec<[f64; 3]> = colourise(options, points, part)?;
+    let grey: Vec<f64> = transfer::normalise(options, points, part.whole)?;
+    let rgba: Vec<[f64; 4]> = colours
+        .iter()
+        .zip(points.iter())
+        .map(|(rgb, point)| {
+            let alpha: f64 = if options.alpha && !point.is_diverged {
+                0.
+            } else {
+                1.
+            };
+            [rgb[0], rgb[1], rgb[2], alpha]
+        })
+        .collect();
+    let mut image: Image = Image {
+        resols: options.resols,
+        depth: options.bit_depth,
+        alpha: options.alpha,
+        rgba,
+        grey,
+        metadata: Manifest::embedded(options, center).records,
     };
-    let _size: usize = match std::io::Write::write(&mut stream, &pixels) {
-        Ok(size) => size,
-        Err(_) => {
-            println!("file write failed");
-            return Err(());
+    // floating-point images are not quantised
+    if !matches!(options.format, Format::Pfm) {
+        dither::dither(&mut image, &options.dither, options.seed);
+    }
+    return Ok(image);
+}
+
+/// Prints the metadata embedded in an image file as `key=value` lines, which can be given to `--from_manifest`.
+///   
+/// * `fname` - Name of the image file.
+pub fn info(fname: &str) -> Result<(), Error> {
+    let bytes: Vec<u8> = match std::fs::read(fname) {
+        Ok(bytes) => bytes,
+        Err(error) => return Err(Error::Read(String::from(fname), error)),
+    };
+    let records: Vec<(String, String)> = if bytes.starts_with(&png::SIGNATURE) {
+        match png::read_text(&bytes) {
+            Ok(records) => records,
+            Err(msg) => return Err(Error::Parse(String::from(fname), msg.into())),
         }
+    } else if [b"P5", b"P6", b"P7"]
+        .iter()
+        .any(|magic| bytes.starts_with(*magic))
+    {
+        netpbm::read_comments(&bytes)
+    } else {
+        return Err(Error::Parse(
+            String::from(fname),
+            String::from("only the Netpbm and PNG formats store metadata").into(),
+        ));
     };
+    if records.is_empty() {
+        return Err(Error::NotFound(format!("{}: no metadata is found", fname)));
+    }
+    println!("# metadata of {}", fname);
+    for (key, value) in records.iter() {
+        println!("{}={}", key, value);
+    }
     return Ok(());
 }
 
-/// Maps a scalar value to an RGB pair
-///   
-/// * `theta` - Random phase angle.
-/// * `val`   - Scalar value.
-fn to_rgb(theta: f64, val: f64) -> [f64; 3] {
-    const PI: f64 = std::f64::consts::PI;
-    return [
-        0.5f64 * val * (1. + f64::sin(0. / 3. * PI + theta)),
-        0.5f64 * val * (1. + f64::sin(2. / 3. * PI + theta)),
-        0.5f64 * val * (1. + f64::sin(4. / 3. * PI + theta)),
-    ];
+#[cfg(test)]
+mod test_output {
+    use crate::output::{quantise, suffixed_fname};
+    #[test]
+    fn test_suffixed_fname() -> () {
+        assert_eq!(
+            "image_00012.ppm",
+            suffixed_fname("image.ppm", "00012").unwrap()
+        );
+        assert_eq!("dir/a.b_0.ppm", suffixed_fname("dir/a.b.ppm", "0").unwrap());
+        assert_eq!(
+            "image_1280x800",
+            suffixed_fname("image", "1280x800").unwrap()
+        );
+        // dots in the directory are kept
+        assert_eq!("dir.d/image_0", suffixed_fname("dir.d/image", "0").unwrap());
+        assert!(suffixed_fname("-", "0").is_err());
+    }
+    #[test]
+    fn test_quantise() -> () {
+        // rounded to the nearest integer instead of truncated
+        assert_eq!(128, quantise(0.5, 255.));
+        assert_eq!(254, quantise(0.998, 255.));
+        assert_eq!(32768, quantise(0.5, 65535.));
+        // clipped to the range
+        assert_eq!(0, quantise(-0.1, 255.));
+        assert_eq!(65535, quantise(1.1, 65535.));
+    }
 }
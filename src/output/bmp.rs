@@ -0,0 +1,124 @@
+#![deny(missing_docs)]
+
+//! Encodes an image in the Windows bitmap (BMP) format.
+//!
+//! Pixels are stored bottom-up in 8 bits per sample: 24-bit BGR,
+//! or 32-bit BGRA with bit fields when the alpha channel is requested.
+//! The `BITMAPV4HEADER` is used so that the alpha channel is recognised.
+
+use crate::error::Error;
+use crate::output::{Image, ImageWriter};
+
+/// Sizes of the file header and the information header.
+const FILE_HEADER_SIZE: usize = 14;
+const INFO_HEADER_SIZE: usize = 108;
+
+/// BMP writer.
+pub struct Bmp;
+
+impl ImageWriter for Bmp {
+    fn encode(&self, image: &Image) -> Result<Vec<u8>, Error> {
+        let bpp: usize = if image.alpha { 4 } else { 3 };
+        // each row is padded to a multiple of 4 bytes
+        let stride: usize = (bpp * image.resols.x).next_multiple_of(4);
+        let offset: usize = FILE_HEADER_SIZE + INFO_HEADER_SIZE;
+        let size: usize = offset + stride * image.resols.y;
+        // the size is stored in signed 32 bits, and the file size in unsigned 32 bits
+        let (width, height, file_size): (i32, i32, u32) = match (
+            i32::try_from(image.resols.x),
+            i32::try_from(image.resols.y),
+            u32::try_from(size),
+        ) {
+            (Ok(width), Ok(height), Ok(file_size)) => (width, height, file_size),
+            _ => {
+                return Err(Error::Argument(format!(
+                    "format: bmp stores at most {} bytes",
+                    u32::MAX
+                )));
+            }
+        };
+        let mut bytes: Vec<u8> = Vec::with_capacity(size);
+        // file header
+        bytes.extend(b"BM");
+        bytes.extend(file_size.to_le_bytes());
+        bytes.extend([0u8; 4]);
+        bytes.extend((offset as u32).to_le_bytes());
+        // information header, positive height for bottom-up rows
+        bytes.extend((INFO_HEADER_SIZE as u32).to_le_bytes());
+        bytes.extend(width.to_le_bytes());
+        bytes.extend(height.to_le_bytes());
+        bytes.extend(1u16.to_le_bytes());
+        bytes.extend((8 * bpp as u16).to_le_bytes());
+        // BI_RGB or BI_BITFIELDS
+        let compression: u32 = if image.alpha { 3 } else { 0 };
+        bytes.extend(compression.to_le_bytes());
+        bytes.extend((file_size - offset as u32).to_le_bytes());
+        // 72 dpi
+        bytes.extend(2835i32.to_le_bytes());
+        bytes.extend(2835i32.to_le_bytes());
+        bytes.extend([0u8; 8]);
+        // masks of red, green, blue, and alpha
+        for mask in [0x00ff0000u32, 0x0000ff00, 0x000000ff, 0xff000000] {
+            bytes.extend(mask.to_le_bytes());
+        }
+        // colour space: sRGB, followed by unused end points and gammas
+        bytes.extend(b"BGRs");
+        bytes.extend([0u8; 48]);
+        // pixels
+        for j in (0..image.resols.y).rev() {
+            let start: usize = bytes.len();
+            for i in 0..image.resols.x {
+                let rgba: [u8; 4] = image.rgba8(j * image.resols.x + i);
+                bytes.extend([rgba[2], rgba[1], rgba[0]]);
+                if image.alpha {
+                    bytes.push(rgba[3]);
+                }
+            }
+            bytes.resize(start + stride, 0);
+        }
+        return Ok(bytes);
+    }
+}
+
+#[cfg(test)]
+mod test_bmp {
+    use crate::coord::Coord;
+    use crate::error::Error;
+    use crate::output::bmp::Bmp;
+    use crate::output::{Image, ImageWriter};
+    /// Decodes the RGBA samples, which are opaque for 24-bit images.
+    fn decode(bytes: &[u8]) -> Vec<[u8; 4]> {
+        let read32 = |n: usize| -> u32 { u32::from_le_bytes(bytes[n..n + 4].try_into().unwrap()) };
+        assert_eq!(b"BM", &bytes[0..2]);
+        assert_eq!(bytes.len(), read32(2) as usize);
+        let offset: usize = read32(10) as usize;
+        let width: usize = read32(18) as usize;
+        let height: usize = read32(22) as usize;
+        let bpp: usize = u16::from_le_bytes([bytes[28], bytes[29]]) as usize / 8;
+        let stride: usize = (bpp * width).next_multiple_of(4);
+        let mut rgba: Vec<[u8; 4]> = Vec::new();
+        for j in (0..height).rev() {
+            for i in 0..width {
+                let n: usize = offset + j * stride + i * bpp;
+                let alpha: u8 = if 4 == bpp { bytes[n + 3] } else { 255 };
+                rgba.push([bytes[n + 2], bytes[n + 1], bytes[n], alpha]);
+            }
+        }
+        return rgba;
+    }
+    #[test]
+    fn test_round_trip() -> () {
+        for (depth, alpha) in [(8u8, false), (8, true), (16, true)] {
+            let image: Image = Image::sample(depth, alpha);
+            let expected: Vec<[u8; 4]> = (0..6).map(|n| image.rgba8(n)).collect();
+            assert_eq!(expected, decode(&Bmp.encode(&image).unwrap()));
+        }
+    }
+    #[test]
+    fn test_too_large() -> () {
+        // neither the header nor the file size can store the size
+        let mut image: Image = Image::sample(8, false);
+        image.resols = Coord { x: 1 << 31, y: 1 };
+        assert!(matches!(Bmp.encode(&image), Err(Error::Argument(_))));
+    }
+}
@@ -0,0 +1,287 @@
+#![deny(missing_docs)]
+
+//! Dithers the colours when they are quantised to the bit depth, so that smooth gradients do not band.
+//!
+//! * `ordered`: thresholds by the 8x8 Bayer matrix.
+//! * `blue_noise`: thresholds by a 64x64 blue-noise mask generated by the void-and-cluster method,
+//!   whose initial pattern is drawn from the random seed.
+//! * `floyd_steinberg`: diffuses the quantisation error to the neighbours.
+//!
+//! The samples are snapped to the levels of the bit depth here,
+//! which are kept by the rounding of the writers.
+//! The alpha channel is left as it is.
+
+use crate::coord::Coord;
+use crate::options::Dither;
+use crate::output::Image;
+use crate::random::Random;
+
+/// Number of bits of the Bayer matrix in each direction.
+const BAYER_BITS: usize = 3;
+
+/// Size of the blue-noise mask in each direction.
+const MASK_SIZE: usize = 64;
+
+/// Width of the Gaussian filter of the void-and-cluster method.
+const MASK_SIGMA: f64 = 1.5;
+
+/// Quantises the colours and the greyscale map to the bit depth with dithering.
+///
+/// * `image`  - Image whose samples are snapped to the levels.
+/// * `method` - Dithering method.
+/// * `seed`   - Random seed giving the blue-noise mask.
+pub fn dither(image: &mut Image, method: &Dither, seed: u64) -> () {
+    let resols: Coord<usize> = image.resols;
+    let max: f64 = image.max() as f64;
+    let thresholds: Option<Vec<f64>> = match method {
+        Dither::None => return,
+        Dither::Ordered => Some(bayer()),
+        Dither::BlueNoise => Some(blue_noise(seed)),
+        Dither::FloydSteinberg => None,
+    };
+    let mut planes: Vec<Vec<f64>> = (0..3)
+        .map(|m| image.rgba.iter().map(|rgba| rgba[m]).collect())
+        .collect();
+    planes.push(image.grey.clone());
+    for (m, plane) in planes.iter_mut().enumerate() {
+        match &thresholds {
+            Some(thresholds) => threshold(plane, &resols, max, thresholds, m),
+            None => diffuse(plane, &resols, max),
+        };
+    }
+    for (n, rgba) in image.rgba.iter_mut().enumerate() {
+        for m in 0..3 {
+            rgba[m] = planes[m][n];
+        }
+    }
+    image.grey = planes.pop().unwrap_or_default();
+}
+
+/// Quantises a channel by comparing the fractions with a tiled threshold map.
+///
+/// * `plane`      - Values of a channel in [0:1].
+/// * `resols`     - Number of pixels in two directions.
+/// * `max`        - Maximum level.
+/// * `thresholds` - Square threshold map in (0:1).
+/// * `channel`    - Index of the channel, by which the map is shifted to decorrelate the channels.
+fn threshold(
+    plane: &mut [f64],
+    resols: &Coord<usize>,
+    max: f64,
+    thresholds: &[f64],
+    channel: usize,
+) -> () {
+    let size: usize = (thresholds.len() as f64).sqrt() as usize;
+    for j in 0..resols.y {
+        for i in 0..resols.x {
+            let n: usize = j * resols.x + i;
+            let t: f64 = thresholds[((j + 29 * channel) % size) * size + (i + 17 * channel) % size];
+            let level: f64 = (max * plane[n].clamp(0., 1.) + t - 0.5).round();
+            plane[n] = level.clamp(0., max) / max;
+        }
+    }
+}
+
+/// Quantises a channel by the Floyd-Steinberg error diffusion, scanning the rows back and forth.
+///
+/// * `plane`  - Values of a channel in [0:1].
+/// * `resols` - Number of pixels in two directions.
+/// * `max`    - Maximum level.
+fn diffuse(plane: &mut [f64], resols: &Coord<usize>, max: f64) -> () {
+    let mut values: Vec<f64> = plane
+        .iter()
+        .map(|value| max * value.clamp(0., 1.))
+        .collect();
+    for j in 0..resols.y {
+        let forward: bool = 0 == j % 2;
+        for k in 0..resols.x {
+            let i: usize = if forward { k } else { resols.x - 1 - k };
+            let n: usize = j * resols.x + i;
+            let level: f64 = values[n].round().clamp(0., max);
+            let error: f64 = values[n] - level;
+            plane[n] = level / max;
+            // neighbours ahead in the scanning direction and in the next row
+            let ahead: Option<usize> = if forward {
+                (i + 1 < resols.x).then_some(i + 1)
+            } else {
+                i.checked_sub(1)
+            };
+            let behind: Option<usize> = if forward {
+                i.checked_sub(1)
+            } else {
+                (i + 1 < resols.x).then_some(i + 1)
+            };
+            if let Some(ahead) = ahead {
+                values[j * resols.x + ahead] += error * 7. / 16.;
+            }
+            if j + 1 < resols.y {
+                let below: usize = (j + 1) * resols.x;
+                if let Some(behind) = behind {
+                    values[below + behind] += error * 3. / 16.;
+                }
+                values[below + i] += error * 5. / 16.;
+                if let Some(ahead) = ahead {
+                    values[below + ahead] += error * 1. / 16.;
+                }
+            }
+        }
+    }
+}
+
+/// Gives the thresholds of the Bayer matrix, row by row.
+fn bayer() -> Vec<f64> {
+    const PATTERN: [[usize; 2]; 2] = [[0, 2], [3, 1]];
+    let size: usize = 1 << BAYER_BITS;
+    let mut thresholds: Vec<f64> = vec![0.; size * size];
+    for j in 0..size {
+        for i in 0..size {
+            // the coarsest pattern is given by the lowest bits
+            let mut rank: usize = 0;
+            for bit in 0..BAYER_BITS {
+                rank = (rank << 2) | PATTERN[(j >> bit) & 1][(i >> bit) & 1];
+            }
+            thresholds[j * size + i] = (rank as f64 + 0.5) / (size * size) as f64;
+        }
+    }
+    return thresholds;
+}
+
+/// Gives the thresholds of a blue-noise mask, row by row, generated by the void-and-cluster method.
+///
+/// * `seed` - Random seed giving the initial pattern.
+fn blue_noise(seed: u64) -> Vec<f64> {
+    let nitems: usize = MASK_SIZE * MASK_SIZE;
+    // Gaussian filter on the periodic domain
+    let kernel: Vec<f64> = (0..nitems)
+        .map(|n| {
+            let distance = |d: usize| -> f64 { usize::min(d, MASK_SIZE - d) as f64 };
+            let (dx, dy): (f64, f64) = (distance(n % MASK_SIZE), distance(n / MASK_SIZE));
+            f64::exp(-(dx * dx + dy * dy) / (2. * MASK_SIGMA * MASK_SIGMA))
+        })
+        .collect();
+    let update = |energy: &mut Vec<f64>, n: usize, sign: f64| -> () {
+        let (x, y): (usize, usize) = (n % MASK_SIZE, n / MASK_SIZE);
+        for (m, value) in energy.iter_mut().enumerate() {
+            let dx: usize = (m % MASK_SIZE + MASK_SIZE - x) % MASK_SIZE;
+            let dy: usize = (m / MASK_SIZE + MASK_SIZE - y) % MASK_SIZE;
+            *value += sign * kernel[dy * MASK_SIZE + dx];
+        }
+    };
+    // tightest cluster among the ones, or largest void among the zeros
+    let find = |energy: &Vec<f64>, pattern: &Vec<bool>, is_cluster: bool| -> usize {
+        let mut best: usize = 0;
+        let mut found: bool = false;
+        for n in 0..nitems {
+            if pattern[n] != is_cluster {
+                continue;
+            }
+            let is_better: bool = if is_cluster {
+                energy[best] < energy[n]
+            } else {
+                energy[n] < energy[best]
+            };
+            if !found || is_better {
+                best = n;
+                found = true;
+            }
+        }
+        return best;
+    };
+    // initial pattern with a tenth of the pixels
+    let mut random: Random = Random::new(seed);
+    let mut pattern: Vec<bool> = vec![false; nitems];
+    let mut energy: Vec<f64> = vec![0.; nitems];
+    let nones: usize = nitems / 10;
+    let mut count: usize = 0;
+    while count < nones {
+        let n: usize = random.gen_integer(0, nitems as u64) as usize;
+        if !pattern[n] {
+            pattern[n] = true;
+            update(&mut energy, n, 1.);
+            count += 1;
+        }
+    }
+    // move the ones from the clusters to the voids until they are spread evenly
+    loop {
+        let cluster: usize = find(&energy, &pattern, true);
+        pattern[cluster] = false;
+        update(&mut energy, cluster, -1.);
+        let void: usize = find(&energy, &pattern, false);
+        pattern[void] = true;
+        update(&mut energy, void, 1.);
+        if cluster == void {
+            break;
+        }
+    }
+    let mut ranks: Vec<usize> = vec![0; nitems];
+    // rank the ones by removing the tightest clusters
+    let (mut ones, mut ones_energy): (Vec<bool>, Vec<f64>) = (pattern.clone(), energy.clone());
+    for rank in (0..nones).rev() {
+        let cluster: usize = find(&ones_energy, &ones, true);
+        ones[cluster] = false;
+        update(&mut ones_energy, cluster, -1.);
+        ranks[cluster] = rank;
+    }
+    // rank the zeros by filling the largest voids
+    for rank in nones..nitems {
+        let void: usize = find(&energy, &pattern, false);
+        pattern[void] = true;
+        update(&mut energy, void, 1.);
+        ranks[void] = rank;
+    }
+    return ranks
+        .iter()
+        .map(|rank| (*rank as f64 + 0.5) / nitems as f64)
+        .collect();
+}
+
+#[cfg(test)]
+mod test_dither {
+    use crate::coord::Coord;
+    use crate::options::Dither;
+    use crate::output::dither::{bayer, blue_noise, dither};
+    use crate::output::Image;
+    /// Sorts the ranks of a threshold map, which should be a permutation.
+    fn ranks(thresholds: &[f64]) -> Vec<usize> {
+        let nitems: usize = thresholds.len();
+        let mut ranks: Vec<usize> = thresholds
+            .iter()
+            .map(|t| (t * nitems as f64 - 0.5).round() as usize)
+            .collect();
+        ranks.sort_unstable();
+        return ranks;
+    }
+    #[test]
+    fn test_bayer() -> () {
+        let thresholds: Vec<f64> = bayer();
+        assert_eq!((0..64).collect::<Vec<usize>>(), ranks(&thresholds));
+        // the first row of the 8x8 Bayer matrix
+        let first: Vec<usize> = thresholds[0..8]
+            .iter()
+            .map(|t| (t * 64. - 0.5).round() as usize)
+            .collect();
+        assert_eq!(vec![0, 32, 8, 40, 2, 34, 10, 42], first);
+    }
+    #[test]
+    fn test_blue_noise() -> () {
+        let thresholds: Vec<f64> = blue_noise(0);
+        assert_eq!((0..4096).collect::<Vec<usize>>(), ranks(&thresholds));
+        // deterministic for a given seed
+        assert_eq!(thresholds, blue_noise(0));
+    }
+    #[test]
+    fn test_dither() -> () {
+        // a flat colour between two levels keeps its mean
+        for method in [Dither::Ordered, Dither::BlueNoise, Dither::FloydSteinberg] {
+            let mut image: Image = Image::sample(8, false);
+            image.resols = Coord { x: 64, y: 64 };
+            image.rgba = vec![[100.3 / 255., 0., 1., 1.]; 64 * 64];
+            image.grey = vec![0.; 64 * 64];
+            dither(&mut image, &method, 0);
+            let levels: Vec<u8> = (0..64 * 64).map(|n| image.rgba8(n)[0]).collect();
+            assert!(levels.iter().all(|level| 100 == *level || 101 == *level));
+            let mean: f64 = levels.iter().map(|level| *level as f64).sum::<f64>() / 4096.;
+            assert!((mean - 100.3).abs() < 0.02);
+        }
+    }
+}
@@ -0,0 +1,55 @@
+#![deny(missing_docs)]
+
+//! Encodes an image in the [farbfeld](https://tools.suckless.org/farbfeld/) format.
+//!
+//! Pixels are always stored as 16-bit big-endian RGBA,
+//! whose alpha is opaque unless the alpha channel is requested.
+
+use crate::error::Error;
+use crate::output::{Image, ImageWriter};
+
+/// farbfeld writer.
+pub struct Farbfeld;
+
+impl ImageWriter for Farbfeld {
+    fn encode(&self, image: &Image) -> Result<Vec<u8>, Error> {
+        let nitems: usize = image.resols.x * image.resols.y;
+        let mut bytes: Vec<u8> = Vec::with_capacity(16 + 8 * nitems);
+        bytes.extend(b"farbfeld");
+        bytes.extend((image.resols.x as u32).to_be_bytes());
+        bytes.extend((image.resols.y as u32).to_be_bytes());
+        for n in 0..nitems {
+            for value in image.rgba16(n) {
+                bytes.extend(value.to_be_bytes());
+            }
+        }
+        return Ok(bytes);
+    }
+}
+
+#[cfg(test)]
+mod test_farbfeld {
+    use crate::output::farbfeld::Farbfeld;
+    use crate::output::{Image, ImageWriter};
+    /// Decodes the RGBA samples.
+    fn decode(bytes: &[u8]) -> Vec<[u16; 4]> {
+        assert_eq!(b"farbfeld", &bytes[0..8]);
+        let width: usize = u32::from_be_bytes(bytes[8..12].try_into().unwrap()) as usize;
+        let height: usize = u32::from_be_bytes(bytes[12..16].try_into().unwrap()) as usize;
+        assert_eq!(16 + 8 * width * height, bytes.len());
+        return bytes[16..]
+            .chunks(8)
+            .map(|chunk| [0, 1, 2, 3].map(|m| u16::from_be_bytes([chunk[2 * m], chunk[2 * m + 1]])))
+            .collect();
+    }
+    #[test]
+    fn test_round_trip() -> () {
+        for (depth, alpha) in [(8u8, false), (8, true), (16, false), (16, true)] {
+            let image: Image = Image::sample(depth, alpha);
+            let expected: Vec<[u16; 4]> = (0..6).map(|n| image.rgba16(n)).collect();
+            assert_eq!(expected, decode(&Farbfeld.encode(&image).unwrap()));
+        }
+        // 8-bit samples are scaled to the full range
+        assert_eq!(65535, Image::sample(8, false).rgba16(0)[3]);
+    }
+}
@@ -0,0 +1,213 @@
+#![deny(missing_docs)]
+
+//! Encodes an image in the [Netpbm](https://netpbm.sourceforge.net/doc/) formats.
+//!
+//! * PPM (`P6`): RGB, the alpha channel is dropped.
+//! * PGM (`P5`): greyscale iteration map.
+//! * PAM (`P7`): RGB or RGBA.
+//!
+//! Samples are stored in 8 bits, or in 16 bits (big-endian) when the maximum value exceeds 255.
+//! The metadata are written as `# key=value` comment lines following the magic number.
+
+use crate::error::Error;
+use crate::output::{Image, ImageWriter};
+
+/// Binary PPM writer.
+pub struct Ppm;
+
+/// Binary PGM writer.
+pub struct Pgm;
+
+/// PAM writer.
+pub struct Pam;
+
+impl ImageWriter for Ppm {
+    fn encode(&self, image: &Image) -> Result<Vec<u8>, Error> {
+        const MAGIC_NUMBER: &str = "P6";
+        let header: String = format!(
+            "{}\n{}{} {}\n{}\n",
+            MAGIC_NUMBER,
+            comments(image),
+            image.resols.x,
+            image.resols.y,
+            image.max()
+        );
+        let samples = (0..image.rgba.len()).flat_map(|n| image.samples(n)[0..3].to_vec());
+        return Ok(write(header, image, samples));
+    }
+}
+
+impl ImageWriter for Pgm {
+    fn encode(&self, image: &Image) -> Result<Vec<u8>, Error> {
+        const MAGIC_NUMBER: &str = "P5";
+        let header: String = format!(
+            "{}\n{}{} {}\n{}\n",
+            MAGIC_NUMBER,
+            comments(image),
+            image.resols.x,
+            image.resols.y,
+            image.max()
+        );
+        let samples = (0..image.grey.len()).map(|n| image.grey_sample(n));
+        return Ok(write(header, image, samples));
+    }
+}
+
+impl ImageWriter for Pam {
+    fn encode(&self, image: &Image) -> Result<Vec<u8>, Error> {
+        const MAGIC_NUMBER: &str = "P7";
+        let (depth, tupltype): (usize, &str) = if image.alpha {
+            (4, "RGB_ALPHA")
+        } else {
+            (3, "RGB")
+        };
+        let header: String = format!(
+            "{}\n{}WIDTH {}\nHEIGHT {}\nDEPTH {}\nMAXVAL {}\nTUPLTYPE {}\nENDHDR\n",
+            MAGIC_NUMBER,
+            comments(image),
+            image.resols.x,
+            image.resols.y,
+            depth,
+            image.max(),
+            tupltype
+        );
+        let samples = (0..image.rgba.len()).flat_map(|n| image.samples(n)[0..depth].to_vec());
+        return Ok(write(header, image, samples));
+    }
+}
+
+/// Converts the metadata to comment lines.
+///
+/// * `image` - Image giving the metadata.
+fn comments(image: &Image) -> String {
+    return image
+        .metadata
+        .iter()
+        .map(|(key, value)| format!("# {}={}\n", key, value))
+        .collect();
+}
+
+/// Reads the `key=value` comment lines following the magic number, where the other comments are skipped.
+///
+/// * `bytes` - Whole contents of the image file.
+pub fn read_comments(bytes: &[u8]) -> Vec<(String, String)> {
+    let mut records: Vec<(String, String)> = Vec::new();
+    for line in bytes.split(|byte| b'\n' == *byte).skip(1) {
+        let comment: &[u8] = match line.strip_prefix(b"#") {
+            Some(comment) => comment,
+            None => break,
+        };
+        let comment: &str = match std::str::from_utf8(comment) {
+            Ok(comment) => comment,
+            Err(_) => continue,
+        };
+        if let Some((key, value)) = comment.split_once('=') {
+            records.push((String::from(key.trim()), String::from(value.trim())));
+        }
+    }
+    return records;
+}
+
+/// Appends the samples to the header, in 1 or 2 bytes following the bit depth.
+///
+/// * `header`  - Text header.
+/// * `image`   - Image giving the bit depth.
+/// * `samples` - Samples to be stored in this order.
+fn write(header: String, image: &Image, samples: impl Iterator<Item = u16>) -> Vec<u8> {
+    let mut bytes: Vec<u8> = header.into_bytes();
+    for sample in samples {
+        if 16 == image.depth {
+            bytes.extend(sample.to_be_bytes());
+        } else {
+            bytes.push(sample as u8);
+        }
+    }
+    return bytes;
+}
+
+#[cfg(test)]
+mod test_netpbm {
+    use crate::output::netpbm::{read_comments, Pam, Pgm, Ppm};
+    use crate::output::{Image, ImageWriter};
+    /// Splits the header into tokens and reads the samples following it.
+    fn decode(bytes: &[u8], ntokens: usize, nbytes: usize) -> (Vec<String>, Vec<u16>) {
+        let mut tokens: Vec<String> = Vec::new();
+        let mut n: usize = 0;
+        while tokens.len() < ntokens {
+            let end: usize = n + bytes[n..]
+                .iter()
+                .position(|byte| byte.is_ascii_whitespace())
+                .unwrap();
+            tokens.push(String::from_utf8(bytes[n..end].to_vec()).unwrap());
+            n = end + 1;
+        }
+        let samples: Vec<u16> = bytes[n..]
+            .chunks(nbytes)
+            .map(|chunk| {
+                if 2 == nbytes {
+                    u16::from_be_bytes([chunk[0], chunk[1]])
+                } else {
+                    chunk[0] as u16
+                }
+            })
+            .collect();
+        return (tokens, samples);
+    }
+    #[test]
+    fn test_ppm() -> () {
+        for depth in [8u8, 16] {
+            let image: Image = Image::sample(depth, true);
+            let (tokens, samples) = decode(&Ppm.encode(&image).unwrap(), 4, depth as usize / 8);
+            assert_eq!(vec!["P6", "3", "2", &image.max().to_string()], tokens);
+            let expected: Vec<u16> = (0..6)
+                .flat_map(|n| image.samples(n)[0..3].to_vec())
+                .collect();
+            assert_eq!(expected, samples);
+        }
+    }
+    #[test]
+    fn test_pgm() -> () {
+        for depth in [8u8, 16] {
+            let image: Image = Image::sample(depth, false);
+            let (tokens, samples) = decode(&Pgm.encode(&image).unwrap(), 4, depth as usize / 8);
+            assert_eq!(vec!["P5", "3", "2", &image.max().to_string()], tokens);
+            let expected: Vec<u16> = (0..6).map(|n| image.grey_sample(n)).collect();
+            assert_eq!(expected, samples);
+        }
+    }
+    #[test]
+    fn test_pam() -> () {
+        for (depth, alpha) in [(8u8, false), (8, true), (16, false), (16, true)] {
+            let image: Image = Image::sample(depth, alpha);
+            let (tokens, samples) = decode(&Pam.encode(&image).unwrap(), 12, depth as usize / 8);
+            let channels: usize = if alpha { 4 } else { 3 };
+            assert_eq!("P7", tokens[0]);
+            assert_eq!(channels.to_string(), tokens[6]);
+            assert_eq!(if alpha { "RGB_ALPHA" } else { "RGB" }, tokens[10]);
+            let expected: Vec<u16> = (0..6)
+                .flat_map(|n| image.samples(n)[0..channels].to_vec())
+                .collect();
+            assert_eq!(expected, samples);
+        }
+    }
+    #[test]
+    fn test_comments() -> () {
+        let mut image: Image = Image::sample(8, false);
+        image.metadata = vec![
+            (String::from("seed"), String::from("5")),
+            (String::from("palette"), String::from("ultra")),
+        ];
+        for bytes in [
+            Ppm.encode(&image).unwrap(),
+            Pgm.encode(&image).unwrap(),
+            Pam.encode(&image).unwrap(),
+        ] {
+            assert_eq!(image.metadata, read_comments(&bytes));
+        }
+        // the header follows the comments
+        let bytes: Vec<u8> = Ppm.encode(&image).unwrap();
+        let n: usize = bytes.windows(6).position(|w| b"ultra\n" == w).unwrap() + 6;
+        let (tokens, _) = decode(&bytes[n..], 3, 1);
+        assert_eq!(vec!["3", "2", "255"], tokens);
+    }
+}
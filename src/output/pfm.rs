@@ -0,0 +1,61 @@
+#![deny(missing_docs)]
+
+//! Encodes an image in the Portable FloatMap (PFM) format.
+//!
+//! Pixels are stored bottom-up as little-endian 32-bit floating-point RGB,
+//! so that no banding is introduced by the quantisation.
+//! The alpha channel and the bit depth are not used.
+
+use crate::error::Error;
+use crate::output::{Image, ImageWriter};
+
+/// PFM writer.
+pub struct Pfm;
+
+impl ImageWriter for Pfm {
+    fn encode(&self, image: &Image) -> Result<Vec<u8>, Error> {
+        const MAGIC_NUMBER: &str = "PF";
+        // negative scale for little-endian samples
+        let header: String = format!(
+            "{}\n{} {}\n-1.0\n",
+            MAGIC_NUMBER, image.resols.x, image.resols.y
+        );
+        let mut bytes: Vec<u8> = header.into_bytes();
+        for j in (0..image.resols.y).rev() {
+            for i in 0..image.resols.x {
+                let rgba: [f64; 4] = image.rgba[j * image.resols.x + i];
+                for value in rgba[0..3].iter() {
+                    bytes.extend((*value as f32).to_le_bytes());
+                }
+            }
+        }
+        return Ok(bytes);
+    }
+}
+
+#[cfg(test)]
+mod test_pfm {
+    use crate::output::pfm::Pfm;
+    use crate::output::{Image, ImageWriter};
+    #[test]
+    fn test_round_trip() -> () {
+        let image: Image = Image::sample(8, false);
+        let bytes: Vec<u8> = Pfm.encode(&image).unwrap();
+        let header: &[u8] = b"PF\n3 2\n-1.0\n";
+        assert_eq!(header, &bytes[0..header.len()]);
+        let values: Vec<f32> = bytes[header.len()..]
+            .chunks(4)
+            .map(|chunk| f32::from_le_bytes(chunk.try_into().unwrap()))
+            .collect();
+        assert_eq!(3 * 6, values.len());
+        // the bottom row comes first
+        for j in 0..2 {
+            for i in 0..3 {
+                for m in 0..3 {
+                    let expected: f32 = image.rgba[j * 3 + i][m] as f32;
+                    assert_eq!(expected, values[((1 - j) * 3 + i) * 3 + m]);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,329 @@
+#![deny(missing_docs)]
+
+//! Encodes an image in the [Portable Network Graphics](https://www.w3.org/TR/png/) format.
+//!
+//! Truecolour images (RGB or RGBA) with 8 or 16 bits per sample are supported.
+//! Each scanline is filtered by the one giving the smallest sum of absolute differences,
+//! and the result is compressed by [`crate::output::zlib`].
+//! The metadata are written as `tEXt` chunks, or as uncompressed `iTXt` chunks when they are not in ASCII.
+
+use crate::coord::Coord;
+use crate::error::Error;
+use crate::output::{zlib, Image, ImageWriter};
+
+/// Signature at the beginning of every PNG file.
+pub const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+/// Lookup table of the CRC-32 used by PNG, whose polynomial is `0xedb88320` (reversed).
+const CRC_TABLE: [u32; 256] = {
+    let mut table: [u32; 256] = [0; 256];
+    let mut n: usize = 0;
+    while n < 256 {
+        let mut c: u32 = n as u32;
+        let mut k: usize = 0;
+        while k < 8 {
+            c = if 1 == c & 1 {
+                0xedb88320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+            k += 1;
+        }
+        table[n] = c;
+        n += 1;
+    }
+    table
+};
+
+/// PNG writer.
+pub struct Png;
+
+impl ImageWriter for Png {
+    fn encode(&self, image: &Image) -> Result<Vec<u8>, Error> {
+        let channels: usize = if image.alpha { 4 } else { 3 };
+        let mut samples: Vec<u8> = Vec::new();
+        for n in 0..image.rgba.len() {
+            for value in image.samples(n)[0..channels].iter() {
+                if 16 == image.depth {
+                    samples.extend(value.to_be_bytes());
+                } else {
+                    samples.push(*value as u8);
+                }
+            }
+        }
+        return Ok(encode(
+            &image.resols,
+            image.depth,
+            channels,
+            &samples,
+            &image.metadata,
+        ));
+    }
+}
+
+/// Encodes samples.
+///
+/// * `resols`   - The number of pixels in two directions.
+/// * `depth`    - Number of bits per sample, 8 or 16.
+/// * `channels` - Number of samples per pixel, 3 (RGB) or 4 (RGBA).
+/// * `samples`  - Samples stored row by row from the top, big-endian when 16 bits.
+/// * `text`     - Key-value records embedded as texts.
+pub fn encode(
+    resols: &Coord<usize>,
+    depth: u8,
+    channels: usize,
+    samples: &[u8],
+    text: &[(String, String)],
+) -> Vec<u8> {
+    let colour_type: u8 = if 4 == channels { 6 } else { 2 };
+    let mut header: Vec<u8> = Vec::new();
+    header.extend((resols.x as u32).to_be_bytes());
+    header.extend((resols.y as u32).to_be_bytes());
+    // compression, filter, and interlace methods
+    header.extend([depth, colour_type, 0, 0, 0]);
+    let bpp: usize = channels * depth as usize / 8;
+    let filtered: Vec<u8> = filter(resols.x * bpp, bpp, samples);
+    let mut png: Vec<u8> = SIGNATURE.to_vec();
+    write_chunk(&mut png, b"IHDR", &header);
+    for (key, value) in text.iter() {
+        let mut data: Vec<u8> = Vec::from(key.as_bytes());
+        data.push(0);
+        if value.is_ascii() {
+            data.extend(value.as_bytes());
+            write_chunk(&mut png, b"tEXt", &data);
+        } else {
+            // uncompressed, without the language tag and the translated keyword
+            data.extend([0, 0, 0, 0]);
+            data.extend(value.as_bytes());
+            write_chunk(&mut png, b"iTXt", &data);
+        }
+    }
+    write_chunk(&mut png, b"IDAT", &zlib::compress(&filtered));
+    write_chunk(&mut png, b"IEND", &[]);
+    return png;
+}
+
+/// Appends a chunk: length, type, data, and the CRC of the type and the data.
+///
+/// * `png`   - Output stream.
+/// * `kind`  - Chunk type.
+/// * `data`  - Chunk data.
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) -> () {
+    png.extend((data.len() as u32).to_be_bytes());
+    let start: usize = png.len();
+    png.extend(kind);
+    png.extend(data);
+    let crc: u32 = crc32(&png[start..]);
+    png.extend(crc.to_be_bytes());
+}
+
+/// Reads the key-value records of the `tEXt` and the uncompressed `iTXt` chunks.
+///
+/// * `png` - Whole contents of the PNG file.
+pub fn read_text(png: &[u8]) -> Result<Vec<(String, String)>, String> {
+    if !png.starts_with(&SIGNATURE) {
+        return Err(String::from("not a PNG file"));
+    }
+    // texts in Latin-1
+    let latin1 = |bytes: &[u8]| -> String { bytes.iter().map(|byte| *byte as char).collect() };
+    let mut records: Vec<(String, String)> = Vec::new();
+    let mut n: usize = SIGNATURE.len();
+    while n + 8 <= png.len() {
+        let length: usize =
+            u32::from_be_bytes([png[n], png[n + 1], png[n + 2], png[n + 3]]) as usize;
+        let kind: &[u8] = &png[n + 4..n + 8];
+        if png.len() < n + 12 + length {
+            return Err(format!("{} chunk is truncated", latin1(kind)));
+        }
+        let data: &[u8] = &png[n + 8..n + 8 + length];
+        n += 12 + length;
+        let (key, text): (&[u8], &[u8]) = match data.iter().position(|byte| 0 == *byte) {
+            Some(end) => (&data[0..end], &data[end + 1..]),
+            None => (data, &[]),
+        };
+        match kind {
+            b"tEXt" => records.push((latin1(key), latin1(text))),
+            b"iTXt" => {
+                // compression flag, compression method, language tag, and translated keyword precede the text,
+                //   which is skipped when compressed
+                if text.len() < 2 || 0 != text[0] {
+                    continue;
+                }
+                let text: &[u8] = match text[2..].splitn(3, |byte| 0 == *byte).nth(2) {
+                    Some(text) => text,
+                    None => return Err(String::from("iTXt chunk is malformed")),
+                };
+                match std::str::from_utf8(text) {
+                    Ok(text) => records.push((latin1(key), String::from(text))),
+                    Err(_) => return Err(String::from("iTXt chunk is not in UTF-8")),
+                };
+            }
+            b"IEND" => break,
+            _ => {}
+        };
+    }
+    return Ok(records);
+}
+
+/// Computes the CRC-32 of the given bytes.
+///
+/// * `bytes` - Raw bytes.
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let mut c: u32 = 0xffffffff;
+    for byte in bytes.iter() {
+        c = CRC_TABLE[((c ^ *byte as u32) & 0xff) as usize] ^ (c >> 8);
+    }
+    return c ^ 0xffffffff;
+}
+
+/// Filters each scanline and prepends the filter type.
+///
+/// * `stride`  - Number of bytes per scanline.
+/// * `bpp`     - Number of bytes per pixel.
+/// * `samples` - Raw scanlines.
+fn filter(stride: usize, bpp: usize, samples: &[u8]) -> Vec<u8> {
+    let zeros: Vec<u8> = vec![0u8; stride];
+    let mut filtered: Vec<u8> = Vec::with_capacity(samples.len() + samples.len() / stride);
+    for (j, row) in samples.chunks(stride).enumerate() {
+        let prev: &[u8] = if 0 == j {
+            &zeros
+        } else {
+            &samples[(j - 1) * stride..j * stride]
+        };
+        // try all filters and pick the one whose residuals are the smallest
+        let mut best: (u64, u8, Vec<u8>) = (u64::MAX, 0, Vec::new());
+        for kind in 0..5u8 {
+            let residuals: Vec<u8> = (0..stride)
+                .map(|i| {
+                    let a: u8 = if bpp <= i { row[i - bpp] } else { 0 };
+                    let b: u8 = prev[i];
+                    let c: u8 = if bpp <= i { prev[i - bpp] } else { 0 };
+                    row[i].wrapping_sub(predict(kind, a, b, c))
+                })
+                .collect();
+            let cost: u64 = residuals
+                .iter()
+                .map(|&residual| (residual as i8).unsigned_abs() as u64)
+                .sum();
+            if cost < best.0 {
+                best = (cost, kind, residuals);
+            }
+        }
+        filtered.push(best.1);
+        filtered.extend(best.2);
+    }
+    return filtered;
+}
+
+/// Predicts a byte from the neighbours following the filter type.
+///
+/// * `kind` - Filter type: none, sub, up, average, or Paeth.
+/// * `a`    - Byte of the left pixel.
+/// * `b`    - Byte of the upper pixel.
+/// * `c`    - Byte of the upper-left pixel.
+fn predict(kind: u8, a: u8, b: u8, c: u8) -> u8 {
+    return match kind {
+        0 => 0,
+        1 => a,
+        2 => b,
+        3 => ((a as u16 + b as u16) / 2) as u8,
+        _ => {
+            let p: i16 = a as i16 + b as i16 - c as i16;
+            let pa: i16 = (p - a as i16).abs();
+            let pb: i16 = (p - b as i16).abs();
+            let pc: i16 = (p - c as i16).abs();
+            if pa <= pb && pa <= pc {
+                a
+            } else if pb <= pc {
+                b
+            } else {
+                c
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod test_png {
+    use crate::coord::Coord;
+    use crate::output::png::{crc32, encode, predict, read_text, SIGNATURE};
+    use crate::output::zlib;
+    /// Decodes the image header and the samples, checking the CRCs.
+    fn decode(png: &[u8]) -> ([u8; 13], Vec<u8>) {
+        assert_eq!(SIGNATURE, png[0..8]);
+        let mut header: [u8; 13] = [0; 13];
+        let mut compressed: Vec<u8> = Vec::new();
+        let mut n: usize = 8;
+        while n < png.len() {
+            let length: usize = u32::from_be_bytes(png[n..n + 4].try_into().unwrap()) as usize;
+            let kind: &[u8] = &png[n + 4..n + 8];
+            let data: &[u8] = &png[n + 8..n + 8 + length];
+            let crc: u32 =
+                u32::from_be_bytes(png[n + 8 + length..n + 12 + length].try_into().unwrap());
+            assert_eq!(crc32(&png[n + 4..n + 8 + length]), crc);
+            match kind {
+                b"IHDR" => header.copy_from_slice(data),
+                b"IDAT" => compressed.extend(data),
+                _ => {}
+            };
+            n += 12 + length;
+        }
+        let filtered: Vec<u8> = zlib::decompress(&compressed).unwrap();
+        // undo the filters
+        let width: usize = u32::from_be_bytes(header[0..4].try_into().unwrap()) as usize;
+        let bpp: usize = [3, 0, 4][header[9] as usize / 3] * header[8] as usize / 8;
+        let stride: usize = width * bpp;
+        let mut samples: Vec<u8> = Vec::new();
+        for (j, row) in filtered.chunks(stride + 1).enumerate() {
+            for i in 0..stride {
+                let a: u8 = if bpp <= i {
+                    samples[j * stride + i - bpp]
+                } else {
+                    0
+                };
+                let b: u8 = if 0 < j {
+                    samples[(j - 1) * stride + i]
+                } else {
+                    0
+                };
+                let c: u8 = if 0 < j && bpp <= i {
+                    samples[(j - 1) * stride + i - bpp]
+                } else {
+                    0
+                };
+                samples.push(row[1 + i].wrapping_add(predict(row[0], a, b, c)));
+            }
+        }
+        return (header, samples);
+    }
+    #[test]
+    fn test_crc32() -> () {
+        assert_eq!(0xae426082, crc32(b"IEND"));
+    }
+    #[test]
+    fn test_round_trip() -> () {
+        let resols: Coord<usize> = Coord { x: 7, y: 5 };
+        for (depth, channels) in [(8u8, 3usize), (8, 4), (16, 3), (16, 4)] {
+            let nitems: usize = resols.x * resols.y * channels * depth as usize / 8;
+            let samples: Vec<u8> = (0..nitems).map(|n| (n * n % 251) as u8).collect();
+            let (header, decoded) = decode(&encode(&resols, depth, channels, &samples, &[]));
+            assert_eq!([0, 0, 0, 7, 0, 0, 0, 5], header[0..8]);
+            assert_eq!(depth, header[8]);
+            assert_eq!(if 4 == channels { 6 } else { 2 }, header[9]);
+            assert_eq!(samples, decoded);
+        }
+    }
+    #[test]
+    fn test_text() -> () {
+        let text: Vec<(String, String)> = vec![
+            (String::from("seed"), String::from("5")),
+            (String::from("palette"), String::from("パレット.ggr")),
+        ];
+        let samples: Vec<u8> = vec![0u8; 2 * 2 * 3];
+        let png: Vec<u8> = encode(&Coord { x: 2, y: 2 }, 8, 3, &samples, &text);
+        assert_eq!(text, read_text(&png).unwrap());
+        // the samples are not affected
+        assert_eq!(samples, decode(&png).1);
+        assert!(read_text(&png[1..]).is_err());
+    }
+}
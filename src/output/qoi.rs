@@ -0,0 +1,182 @@
+#![deny(missing_docs)]
+
+//! Encodes an image in the [Quite OK Image](https://qoiformat.org/qoi-specification.pdf) format.
+//!
+//! Pixels are stored in 8 bits per sample, RGB or RGBA,
+//! as runs, references to recently seen pixels, small differences, or full values.
+
+use crate::error::Error;
+use crate::output::{Image, ImageWriter};
+
+/// Tags of the operations.
+const OP_INDEX: u8 = 0x00;
+const OP_DIFF: u8 = 0x40;
+const OP_LUMA: u8 = 0x80;
+const OP_RUN: u8 = 0xc0;
+const OP_RGB: u8 = 0xfe;
+const OP_RGBA: u8 = 0xff;
+
+/// Marker closing the stream.
+const END_MARKER: [u8; 8] = [0, 0, 0, 0, 0, 0, 0, 1];
+
+/// QOI writer.
+pub struct Qoi;
+
+impl ImageWriter for Qoi {
+    fn encode(&self, image: &Image) -> Result<Vec<u8>, Error> {
+        let channels: u8 = if image.alpha { 4 } else { 3 };
+        let mut bytes: Vec<u8> = Vec::new();
+        bytes.extend(b"qoif");
+        bytes.extend((image.resols.x as u32).to_be_bytes());
+        bytes.extend((image.resols.y as u32).to_be_bytes());
+        // sRGB with linear alpha
+        bytes.extend([channels, 0]);
+        let mut seen: [[u8; 4]; 64] = [[0; 4]; 64];
+        let mut prev: [u8; 4] = [0, 0, 0, 255];
+        let mut run: u8 = 0;
+        let nitems: usize = image.resols.x * image.resols.y;
+        for n in 0..nitems {
+            let pixel: [u8; 4] = image.rgba8(n);
+            if pixel == prev {
+                run += 1;
+                if 62 == run || nitems - 1 == n {
+                    bytes.push(OP_RUN | (run - 1));
+                    run = 0;
+                }
+                continue;
+            }
+            if 0 < run {
+                bytes.push(OP_RUN | (run - 1));
+                run = 0;
+            }
+            let index: usize = hash(&pixel);
+            if seen[index] == pixel {
+                bytes.push(OP_INDEX | index as u8);
+            } else if pixel[3] != prev[3] {
+                bytes.push(OP_RGBA);
+                bytes.extend(pixel);
+            } else {
+                let dr: i8 = pixel[0].wrapping_sub(prev[0]) as i8;
+                let dg: i8 = pixel[1].wrapping_sub(prev[1]) as i8;
+                let db: i8 = pixel[2].wrapping_sub(prev[2]) as i8;
+                let dr_dg: i8 = dr.wrapping_sub(dg);
+                let db_dg: i8 = db.wrapping_sub(dg);
+                if [dr, dg, db].iter().all(|d| (-2..=1).contains(d)) {
+                    bytes.push(
+                        OP_DIFF
+                            | (((dr + 2) as u8) << 4)
+                            | (((dg + 2) as u8) << 2)
+                            | (db + 2) as u8,
+                    );
+                } else if (-32..=31).contains(&dg)
+                    && (-8..=7).contains(&dr_dg)
+                    && (-8..=7).contains(&db_dg)
+                {
+                    bytes.push(OP_LUMA | (dg + 32) as u8);
+                    bytes.push((((dr_dg + 8) as u8) << 4) | (db_dg + 8) as u8);
+                } else {
+                    bytes.push(OP_RGB);
+                    bytes.extend(&pixel[0..3]);
+                }
+            }
+            seen[index] = pixel;
+            prev = pixel;
+        }
+        bytes.extend(END_MARKER);
+        return Ok(bytes);
+    }
+}
+
+/// Gives the position of a pixel in the array of recently seen pixels.
+///
+/// * `pixel` - RGBA samples.
+fn hash(pixel: &[u8; 4]) -> usize {
+    let [r, g, b, a] = pixel.map(|value| value as usize);
+    return (3 * r + 5 * g + 7 * b + 11 * a) % 64;
+}
+
+#[cfg(test)]
+mod test_qoi {
+    use crate::coord::Coord;
+    use crate::output::qoi::{hash, Qoi, END_MARKER, OP_RGB, OP_RGBA};
+    use crate::output::{Image, ImageWriter};
+    /// Decodes the RGBA samples following the specification.
+    fn decode(bytes: &[u8]) -> Vec<[u8; 4]> {
+        assert_eq!(b"qoif", &bytes[0..4]);
+        let width: usize = u32::from_be_bytes(bytes[4..8].try_into().unwrap()) as usize;
+        let height: usize = u32::from_be_bytes(bytes[8..12].try_into().unwrap()) as usize;
+        let mut seen: [[u8; 4]; 64] = [[0; 4]; 64];
+        let mut pixel: [u8; 4] = [0, 0, 0, 255];
+        let mut rgba: Vec<[u8; 4]> = Vec::new();
+        let mut n: usize = 14;
+        while rgba.len() < width * height {
+            let tag: u8 = bytes[n];
+            n += 1;
+            if OP_RGB == tag {
+                pixel[0..3].copy_from_slice(&bytes[n..n + 3]);
+                n += 3;
+            } else if OP_RGBA == tag {
+                pixel.copy_from_slice(&bytes[n..n + 4]);
+                n += 4;
+            } else {
+                let value: u8 = tag & 0x3f;
+                match tag >> 6 {
+                    0 => pixel = seen[value as usize],
+                    1 => {
+                        pixel[0] = pixel[0].wrapping_add(((value >> 4) & 3).wrapping_sub(2));
+                        pixel[1] = pixel[1].wrapping_add(((value >> 2) & 3).wrapping_sub(2));
+                        pixel[2] = pixel[2].wrapping_add((value & 3).wrapping_sub(2));
+                    }
+                    2 => {
+                        let dg: u8 = value.wrapping_sub(32);
+                        let next: u8 = bytes[n];
+                        n += 1;
+                        pixel[0] =
+                            pixel[0].wrapping_add(dg.wrapping_add(next >> 4).wrapping_sub(8));
+                        pixel[1] = pixel[1].wrapping_add(dg);
+                        pixel[2] =
+                            pixel[2].wrapping_add(dg.wrapping_add(next & 15).wrapping_sub(8));
+                    }
+                    _ => {
+                        for _ in 0..value {
+                            rgba.push(pixel);
+                        }
+                    }
+                };
+            }
+            seen[hash(&pixel)] = pixel;
+            rgba.push(pixel);
+        }
+        assert_eq!(END_MARKER, bytes[n..]);
+        return rgba;
+    }
+    #[test]
+    fn test_round_trip() -> () {
+        for (depth, alpha) in [(8u8, false), (8, true), (16, true)] {
+            let image: Image = Image::sample(depth, alpha);
+            let expected: Vec<[u8; 4]> = (0..6).map(|n| image.rgba8(n)).collect();
+            assert_eq!(expected, decode(&Qoi.encode(&image).unwrap()));
+        }
+    }
+    #[test]
+    fn test_operations() -> () {
+        // runs, small differences, and repeated colours
+        let mut image: Image = Image::sample(8, true);
+        image.resols = Coord { x: 200, y: 1 };
+        image.rgba = (0..200)
+            .map(|i: usize| {
+                let value: u16 = if i < 70 {
+                    0
+                } else {
+                    (i % 5 * 3 + i / 50 * 40) as u16
+                };
+                [value, value + 1, value / 2, if 150 < i { 128 } else { 255 }]
+                    .map(|value| value as f64 / 255.)
+            })
+            .collect();
+        let bytes: Vec<u8> = Qoi.encode(&image).unwrap();
+        assert!(bytes.len() < 14 + 8 + 200 * 4);
+        let expected: Vec<[u8; 4]> = (0..200).map(|n| image.rgba8(n)).collect();
+        assert_eq!(expected, decode(&bytes));
+    }
+}
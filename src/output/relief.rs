@@ -0,0 +1,155 @@
+#![deny(missing_docs)]
+
+//! Shades the colours as if the image were a relief lit by a distant light, which gives an embossed look.
+//!
+//! The height of each pixel of the exterior is given by
+//!
+//! * `smooth`: the continuous number of iterations to diverge, or
+//! * `distance`: the negative binary logarithm of the estimated distance to the set,
+//!
+//! both of which rise towards the set by about one per halving of the distance.
+//! The normals are computed from the central differences of the heights between the neighbouring pixels,
+//! and lit by the Lambertian diffuse and the Blinn-Phong specular reflections,
+//! with the viewer right above the image.
+//! The interior is left as it is.
+
+use crate::coord::Coord;
+use crate::mandelbrot::Point;
+use crate::options::{Options, Relief};
+
+/// Exponent of the Blinn-Phong specular reflection, which narrows the highlights.
+const SHININESS: f64 = 32.;
+
+/// Shades the colours of the exterior following the height field.
+///
+/// * `options` - Fixed parameters: the number of pixels, the height field, the light.
+/// * `points`  - Result of the recurrence relation for each point.
+/// * `colours` - RGB values in [0:1] of each pixel, which are shaded in place.
+pub fn shade(options: &Options, points: &[Point], colours: &mut [[f64; 3]]) -> () {
+    let relief: &Relief = match &options.relief {
+        Some(relief) => relief,
+        None => return,
+    };
+    let resols: &Coord<usize> = &options.resols;
+    let heights: Vec<f64> = points
+        .iter()
+        .map(|point| match relief {
+            Relief::Smooth => point.smooth,
+            // distances not available in the data are regarded as flat
+            Relief::Distance => -f64::max(point.distance, f64::MIN_POSITIVE).log2(),
+        })
+        .collect();
+    let light: [f64; 3] = {
+        let azimuth: f64 = options.light_azimuth.to_radians();
+        let elevation: f64 = options.light_elevation.to_radians();
+        [
+            elevation.cos() * azimuth.cos(),
+            elevation.cos() * azimuth.sin(),
+            elevation.sin(),
+        ]
+    };
+    // half vector between the light and the viewer
+    let half: [f64; 3] = normalise([light[0], light[1], light[2] + 1.]);
+    let normals: Vec<[f64; 3]> = normals(resols, points, &heights, options.relief_strength);
+    for (n, normal) in normals.iter().enumerate() {
+        if !points[n].is_diverged {
+            continue;
+        }
+        let diffuse: f64 = f64::max(dot(normal, &light), 0.);
+        let specular: f64 = if 0. < diffuse {
+            f64::max(dot(normal, &half), 0.).powf(SHININESS)
+        } else {
+            0.
+        };
+        let brightness: f64 = options.ambient + (1. - options.ambient) * diffuse;
+        colours[n] = colours[n].map(|value| {
+            let lit: f64 = value * brightness + options.specular * specular;
+            (value + options.relief_blend * (lit - value)).clamp(0., 1.)
+        });
+    }
+}
+
+/// Computes the unit normal of the height field at each pixel, whose x and y components point to the right and the top.
+///
+/// The neighbours in the interior or outside the image are skipped, giving one-sided differences.
+///
+/// * `resols`   - Number of pixels in two directions.
+/// * `points`   - Result of the recurrence relation for each point.
+/// * `heights`  - Height of each pixel, row by row from the top.
+/// * `strength` - Factor exaggerating the slopes.
+fn normals(
+    resols: &Coord<usize>,
+    points: &[Point],
+    heights: &[f64],
+    strength: f64,
+) -> Vec<[f64; 3]> {
+    let mut normals: Vec<[f64; 3]> = vec![[0., 0., 1.]; resols.x * resols.y];
+    for j in 0..resols.y {
+        for i in 0..resols.x {
+            let n: usize = j * resols.x + i;
+            let neighbour = |di: isize, dj: isize| -> Option<usize> {
+                let i: usize = i.checked_add_signed(di).filter(|i| *i < resols.x)?;
+                let j: usize = j.checked_add_signed(dj).filter(|j| *j < resols.y)?;
+                let m: usize = j * resols.x + i;
+                return points[m].is_diverged.then_some(m);
+            };
+            let slope = |lower: Option<usize>, upper: Option<usize>| -> f64 {
+                return match (lower, upper) {
+                    (Some(lower), Some(upper)) => 0.5 * (heights[upper] - heights[lower]),
+                    (Some(lower), None) => heights[n] - heights[lower],
+                    (None, Some(upper)) => heights[upper] - heights[n],
+                    (None, None) => 0.,
+                };
+            };
+            // the rows are stored from the top
+            let dx: f64 = slope(neighbour(-1, 0), neighbour(1, 0));
+            let dy: f64 = slope(neighbour(0, 1), neighbour(0, -1));
+            normals[n] = normalise([-strength * dx, -strength * dy, 1.]);
+        }
+    }
+    return normals;
+}
+
+/// Gives the inner product of two vectors.
+///
+/// * `a` - A vector.
+/// * `b` - Another vector.
+fn dot(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    return a[0] * b[0] + a[1] * b[1] + a[2] * b[2];
+}
+
+/// Gives the unit vector in the direction of a vector.
+///
+/// * `a` - A non-zero vector.
+fn normalise(a: [f64; 3]) -> [f64; 3] {
+    let norm: f64 = dot(&a, &a).sqrt();
+    return a.map(|value| value / norm);
+}
+
+#[cfg(test)]
+mod test_relief {
+    use crate::coord::Coord;
+    use crate::mandelbrot::Point;
+    use crate::output::relief::normals;
+    use crate::test_util::assert_close;
+    #[test]
+    fn test_normals() -> () {
+        // a plane rising to the right by one per pixel, whose last pixel is in the interior
+        let resols: Coord<usize> = Coord { x: 4, y: 3 };
+        let points: Vec<Point> = (0..12)
+            .map(|n| Point {
+                is_diverged: 11 != n,
+                iter: 1,
+                smooth: 1.,
+                distance: 1.,
+            })
+            .collect();
+        let heights: Vec<f64> = (0..12).map(|n| (n % 4) as f64).collect();
+        let normals: Vec<[f64; 3]> = normals(&resols, &points, &heights, 2.);
+        let expected: [f64; 3] = [-2. / 5f64.sqrt(), 0., 1. / 5f64.sqrt()];
+        for normal in normals.iter() {
+            // the interior is skipped, leaving one-sided differences next to it
+            assert_close(expected, *normal, 1e-12);
+        }
+    }
+}
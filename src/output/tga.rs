@@ -0,0 +1,163 @@
+#![deny(missing_docs)]
+
+//! Encodes an image in the Truevision TGA format.
+//!
+//! Pixels are stored top-down in 8 bits per sample:
+//! 24-bit BGR, or 32-bit BGRA when the alpha channel is requested.
+//! Runs of identical pixels are compressed by the run-length encoding.
+
+use crate::error::Error;
+use crate::output::{Image, ImageWriter};
+
+/// Maximum number of pixels in a packet.
+const MAX_PACKET: usize = 128;
+
+/// TGA writer.
+pub struct Tga;
+
+impl ImageWriter for Tga {
+    fn encode(&self, image: &Image) -> Result<Vec<u8>, Error> {
+        // the size is stored in 16 bits
+        let (width, height): (u16, u16) =
+            match (u16::try_from(image.resols.x), u16::try_from(image.resols.y)) {
+                (Ok(width), Ok(height)) => (width, height),
+                _ => {
+                    return Err(Error::Argument(format!(
+                        "format: tga stores at most {} pixels in each direction",
+                        u16::MAX
+                    )));
+                }
+            };
+        let bpp: usize = if image.alpha { 4 } else { 3 };
+        let mut bytes: Vec<u8> = Vec::new();
+        // no image ID, no colour map, run-length encoded true-colour image
+        bytes.extend([0u8, 0, 10]);
+        bytes.extend([0u8; 5]);
+        // origin
+        bytes.extend([0u8; 4]);
+        bytes.extend(width.to_le_bytes());
+        bytes.extend(height.to_le_bytes());
+        bytes.push(8 * bpp as u8);
+        // top-left origin and the number of alpha bits
+        let alpha_bits: u8 = if image.alpha { 8 } else { 0 };
+        bytes.push(0x20 | alpha_bits);
+        // packets do not cross scanlines
+        for j in 0..image.resols.y {
+            let pixels: Vec<Vec<u8>> = (0..image.resols.x)
+                .map(|i| {
+                    let rgba: [u8; 4] = image.rgba8(j * image.resols.x + i);
+                    [rgba[2], rgba[1], rgba[0], rgba[3]][0..bpp].to_vec()
+                })
+                .collect();
+            write_packets(&mut bytes, &pixels);
+        }
+        return Ok(bytes);
+    }
+}
+
+/// Appends a scanline as run-length packets and raw packets.
+///
+/// * `bytes`  - Output stream.
+/// * `pixels` - Pixels of a scanline in the stored order of the channels.
+fn write_packets(bytes: &mut Vec<u8>, pixels: &[Vec<u8>]) -> () {
+    let mut i: usize = 0;
+    while i < pixels.len() {
+        // length of the run starting here
+        let mut run: usize = 1;
+        while i + run < pixels.len() && run < MAX_PACKET && pixels[i + run] == pixels[i] {
+            run += 1;
+        }
+        if 1 < run {
+            bytes.push(0x80 | (run - 1) as u8);
+            bytes.extend(&pixels[i]);
+            i += run;
+            continue;
+        }
+        // raw pixels until the next run begins
+        let mut count: usize = 1;
+        while i + count < pixels.len()
+            && count < MAX_PACKET
+            && (i + count + 1 == pixels.len() || pixels[i + count] != pixels[i + count + 1])
+        {
+            count += 1;
+        }
+        bytes.push((count - 1) as u8);
+        for pixel in pixels[i..i + count].iter() {
+            bytes.extend(pixel);
+        }
+        i += count;
+    }
+}
+
+#[cfg(test)]
+mod test_tga {
+    use crate::coord::Coord;
+    use crate::error::Error;
+    use crate::output::tga::Tga;
+    use crate::output::{Image, ImageWriter};
+    /// Decodes the RGBA samples, which are opaque for 24-bit images.
+    fn decode(bytes: &[u8]) -> Vec<[u8; 4]> {
+        assert_eq!(10, bytes[2]);
+        let width: usize = u16::from_le_bytes([bytes[12], bytes[13]]) as usize;
+        let height: usize = u16::from_le_bytes([bytes[14], bytes[15]]) as usize;
+        let bpp: usize = bytes[16] as usize / 8;
+        assert_eq!(0x20, bytes[17] & 0x20);
+        let pixel = |n: usize| -> [u8; 4] {
+            let alpha: u8 = if 4 == bpp { bytes[n + 3] } else { 255 };
+            return [bytes[n + 2], bytes[n + 1], bytes[n], alpha];
+        };
+        let mut rgba: Vec<[u8; 4]> = Vec::new();
+        let mut n: usize = 18;
+        while rgba.len() < width * height {
+            let count: usize = (bytes[n] & 0x7f) as usize + 1;
+            if 0x80 == bytes[n] & 0x80 {
+                rgba.extend(vec![pixel(n + 1); count]);
+                n += 1 + bpp;
+            } else {
+                for m in 0..count {
+                    rgba.push(pixel(n + 1 + m * bpp));
+                }
+                n += 1 + count * bpp;
+            }
+        }
+        assert_eq!(bytes.len(), n);
+        return rgba;
+    }
+    #[test]
+    fn test_round_trip() -> () {
+        for (depth, alpha) in [(8u8, false), (8, true), (16, true)] {
+            let image: Image = Image::sample(depth, alpha);
+            let expected: Vec<[u8; 4]> = (0..6).map(|n| image.rgba8(n)).collect();
+            assert_eq!(expected, decode(&Tga.encode(&image).unwrap()));
+        }
+    }
+    #[test]
+    fn test_runs() -> () {
+        // a long run followed by distinct pixels and a short run
+        let mut image: Image = Image::sample(8, false);
+        image.resols = Coord { x: 300, y: 1 };
+        image.rgba = (0..300)
+            .map(|i: usize| {
+                let value: u16 = if i < 200 {
+                    7
+                } else if i < 290 {
+                    i as u16
+                } else {
+                    9
+                };
+                [value, value, value, 255].map(|value| value as f64 / 255.)
+            })
+            .collect();
+        let bytes: Vec<u8> = Tga.encode(&image).unwrap();
+        assert!(bytes.len() < 18 + 300 * 3);
+        let expected: Vec<[u8; 4]> = (0..300).map(|n| image.rgba8(n)).collect();
+        assert_eq!(expected, decode(&bytes));
+    }
+    #[test]
+    fn test_too_large() -> () {
+        // the header cannot store the width
+        let mut image: Image = Image::sample(8, false);
+        image.resols = Coord { x: 65536, y: 1 };
+        assert!(matches!(Tga.encode(&image), Err(Error::Argument(_))));
+    }
+}
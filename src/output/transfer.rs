@@ -0,0 +1,122 @@
+#![deny(missing_docs)]
+
+//! Maps the number of iterations to a scalar value in [0:1], which gives the position on the palette.
+//!
+//! The number of iterations of the exterior is first scaled linearly between two bounds,
+//! which are the extrema of all pixels by default or the given percentiles of the exterior,
+//! taken over the whole view when the image is a part of it,
+//! and then reshaped by a transfer function (see [`crate::options::Transfer`]).
+//! The interior is always mapped to unity.
+
+use crate::error::Error;
+use crate::mandelbrot::Point;
+use crate::options::{Options, Transfer};
+
+/// Converts the number of iterations to a scalar value in [0:1],
+/// which is unity for the points which do not diverge
+///   
+/// * `options` - Fixed parameters: the transfer function and the percentiles
+/// * `points`  - Result of the recurrence relation for each point
+/// * `whole`   - Result of the recurrence relation over the whole view, from which the bounds are taken
+pub fn normalise(options: &Options, points: &[Point], whole: &[Point]) -> Result<Vec<f64>, Error> {
+    // find extrema
+    let min: u64 = match whole.iter().min_by_key(|point| point.iter) {
+        Some(point) => point.iter,
+        None => {
+            return Err(Error::Argument(String::from("the image has no pixels")));
+        }
+    };
+    let max: u64 = match whole.iter().max_by_key(|point| point.iter) {
+        Some(point) => point.iter,
+        None => {
+            return Err(Error::Argument(String::from("the image has no pixels")));
+        }
+    };
+    let mut iters: Vec<u64> = whole
+        .iter()
+        .filter(|point| point.is_diverged)
+        .map(|point| point.iter)
+        .collect();
+    iters.sort_unstable();
+    // replace the extrema by the percentiles of the exterior
+    let (min, max): (f64, f64) = match options.clip {
+        Some(clip) if !iters.is_empty() => {
+            (percentile(&iters, clip[0]), percentile(&iters, clip[1]))
+        }
+        _ => (min as f64, max as f64),
+    };
+    let scale = |iter: u64| -> f64 {
+        if max <= min {
+            return 0.;
+        }
+        return ((iter as f64 - min) / (max - min)).clamp(0., 1.);
+    };
+    // cumulative distribution of the scaled values for the equalisation
+    let cdf: Vec<f64> = iters.iter().map(|iter| scale(*iter)).collect();
+    let nfirst: usize = match cdf.first() {
+        Some(first) => cdf.partition_point(|val| val <= first),
+        None => 0,
+    };
+    return Ok(points
+        .iter()
+        .map(|point| {
+            if !point.is_diverged {
+                return 1.;
+            }
+            let val: f64 = scale(point.iter);
+            match options.transfer {
+                Transfer::Linear => val,
+                Transfer::Log => {
+                    // logarithm of the number of iterations from the lower bound
+                    let span: f64 = max - min;
+                    if 0. < span {
+                        f64::ln(1. + span * val) / f64::ln(1. + span)
+                    } else {
+                        val
+                    }
+                }
+                Transfer::Sqrt => val.sqrt(),
+                Transfer::Power => val.powf(options.transfer_exponent),
+                Transfer::Arctan => {
+                    f64::atan(options.transfer_steepness * val)
+                        / f64::atan(options.transfer_steepness)
+                }
+                Transfer::Equalise => {
+                    let nitems: usize = cdf.len();
+                    if nitems <= nfirst {
+                        0.
+                    } else {
+                        let rank: usize = cdf.partition_point(|other| *other <= val);
+                        (rank - nfirst) as f64 / (nitems - nfirst) as f64
+                    }
+                }
+            }
+        })
+        .collect());
+}
+
+/// Gives a percentile of sorted values, interpolating linearly between the neighbours.
+///
+/// * `values`  - Non-empty values sorted in the ascending order.
+/// * `percent` - Percentile in [0:100].
+fn percentile(values: &[u64], percent: f64) -> f64 {
+    let position: f64 = 0.01 * percent * (values.len() - 1) as f64;
+    let lower: usize = position.floor() as usize;
+    let upper: usize = usize::min(lower + 1, values.len() - 1);
+    let frac: f64 = position - lower as f64;
+    return (1. - frac) * values[lower] as f64 + frac * values[upper] as f64;
+}
+
+#[cfg(test)]
+mod test_transfer {
+    use crate::output::transfer::percentile;
+    #[test]
+    fn test_percentile() -> () {
+        let values: [u64; 5] = [1, 2, 3, 4, 100];
+        assert_eq!(1., percentile(&values, 0.));
+        assert_eq!(3., percentile(&values, 50.));
+        assert_eq!(100., percentile(&values, 100.));
+        assert_eq!(3.5, percentile(&values, 62.5));
+        assert_eq!(7., percentile(&[7], 30.));
+    }
+}
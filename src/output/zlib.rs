@@ -0,0 +1,378 @@
+#![deny(missing_docs)]
+
+//! Compresses data in the zlib format ([RFC 1950](https://www.rfc-editor.org/rfc/rfc1950)).
+//!
+//! The payload is compressed by the deflate algorithm ([RFC 1951](https://www.rfc-editor.org/rfc/rfc1951)):
+//! repeated byte sequences are replaced by back-references found by hash chains (LZ77),
+//! which are encoded with the fixed Huffman codes.
+
+/// Size of the sliding window of the back-references.
+const WINDOW: usize = 32768;
+
+/// Shortest and longest back-references.
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+
+/// Number of candidates examined for each position.
+/// NOTE: the larger the better compression, the smaller the faster
+const MAX_CHAIN: usize = 64;
+
+/// Number of bits of the hash of three consecutive bytes.
+const HASH_BITS: usize = 15;
+
+/// Base lengths of the length codes 257-285 and the numbers of their extra bits.
+const LENGTH_BASES: [usize; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRAS: [u32; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+
+/// Base distances of the distance codes 0-29 and the numbers of their extra bits.
+const DISTANCE_BASES: [usize; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRAS: [u32; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+/// Packs bits into bytes from the least significant bit.
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u64,
+    nbits: u32,
+}
+
+impl BitWriter {
+    /// Appends the lowest `nbits` bits of `bits`, the least significant bit first.
+    fn write(&mut self, bits: u32, nbits: u32) -> () {
+        self.buffer |= (bits as u64) << self.nbits;
+        self.nbits += nbits;
+        while 8 <= self.nbits {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.nbits -= 8;
+        }
+    }
+
+    /// Appends a Huffman code, which is stored from the most significant bit.
+    fn write_code(&mut self, code: u32, nbits: u32) -> () {
+        let reversed: u32 = code.reverse_bits() >> (32 - nbits);
+        self.write(reversed, nbits);
+    }
+
+    /// Pads the last byte with zeros and returns the bytes.
+    fn finish(mut self) -> Vec<u8> {
+        if 0 < self.nbits {
+            self.bytes.push(self.buffer as u8);
+        }
+        return self.bytes;
+    }
+}
+
+/// Compresses the given data into a zlib stream.
+///
+/// * `data` - Raw bytes to be compressed.
+pub fn compress(data: &[u8]) -> Vec<u8> {
+    // deflate with 32 KiB window, default compression level
+    let mut stream: Vec<u8> = vec![0x78, 0x9c];
+    stream.extend(deflate(data));
+    stream.extend(adler32(data).to_be_bytes());
+    return stream;
+}
+
+/// Compresses the given data into a single deflate block using the fixed Huffman codes.
+///
+/// * `data` - Raw bytes to be compressed.
+fn deflate(data: &[u8]) -> Vec<u8> {
+    let mut writer: BitWriter = BitWriter {
+        bytes: Vec::new(),
+        buffer: 0,
+        nbits: 0,
+    };
+    // final block, fixed Huffman codes
+    writer.write(1, 1);
+    writer.write(1, 2);
+    // heads of the hash chains and links to the previous positions having the same hash
+    let mut heads: Vec<usize> = vec![usize::MAX; 1 << HASH_BITS];
+    let mut prevs: Vec<usize> = vec![usize::MAX; WINDOW];
+    let hash = |n: usize| -> usize {
+        let key: usize =
+            ((data[n] as usize) << 16) | ((data[n + 1] as usize) << 8) | (data[n + 2] as usize);
+        return (key.wrapping_mul(2654435761) >> 8) & ((1 << HASH_BITS) - 1);
+    };
+    let insert = |n: usize, heads: &mut Vec<usize>, prevs: &mut Vec<usize>| -> () {
+        if n + MIN_MATCH <= data.len() {
+            let h: usize = hash(n);
+            prevs[n % WINDOW] = heads[h];
+            heads[h] = n;
+        }
+    };
+    let mut n: usize = 0;
+    while n < data.len() {
+        let (length, distance): (usize, usize) = find_match(data, n, &heads, &prevs, hash);
+        if MIN_MATCH <= length {
+            write_match(&mut writer, length, distance);
+            for m in n..n + length {
+                insert(m, &mut heads, &mut prevs);
+            }
+            n += length;
+        } else {
+            write_literal(&mut writer, data[n] as u32);
+            insert(n, &mut heads, &mut prevs);
+            n += 1;
+        }
+    }
+    // end of block
+    write_literal(&mut writer, 256);
+    return writer.finish();
+}
+
+/// Finds the longest back-reference for the given position.
+///
+/// Returns the length and the distance, whose length is zero when nothing is found.
+///
+/// * `data`  - Raw bytes to be compressed.
+/// * `n`     - Current position.
+/// * `heads` - Latest positions for each hash.
+/// * `prevs` - Previous positions having the same hash.
+/// * `hash`  - Hash function of three bytes starting at a position.
+fn find_match(
+    data: &[u8],
+    n: usize,
+    heads: &[usize],
+    prevs: &[usize],
+    hash: impl Fn(usize) -> usize,
+) -> (usize, usize) {
+    if data.len() < n + MIN_MATCH {
+        return (0, 0);
+    }
+    let max_length: usize = usize::min(MAX_MATCH, data.len() - n);
+    let mut best: (usize, usize) = (0, 0);
+    let mut candidate: usize = heads[hash(n)];
+    for _ in 0..MAX_CHAIN {
+        // the chain is broken or out of the window
+        if usize::MAX == candidate || WINDOW < n - candidate {
+            break;
+        }
+        let mut length: usize = 0;
+        while length < max_length && data[candidate + length] == data[n + length] {
+            length += 1;
+        }
+        if best.0 < length {
+            best = (length, n - candidate);
+            if max_length == length {
+                break;
+            }
+        }
+        let prev: usize = prevs[candidate % WINDOW];
+        // older positions only, otherwise the slot has been overwritten
+        if usize::MAX != prev && candidate <= prev {
+            break;
+        }
+        candidate = prev;
+    }
+    return best;
+}
+
+/// Writes a literal byte or the end-of-block marker with the fixed Huffman code.
+///
+/// * `writer` - Output bit stream.
+/// * `symbol` - Literal / length symbol, in [0:287].
+fn write_literal(writer: &mut BitWriter, symbol: u32) -> () {
+    match symbol {
+        0..=143 => writer.write_code(0x30 + symbol, 8),
+        144..=255 => writer.write_code(0x190 + symbol - 144, 9),
+        256..=279 => writer.write_code(symbol - 256, 7),
+        _ => writer.write_code(0xc0 + symbol - 280, 8),
+    };
+}
+
+/// Writes a back-reference with the fixed Huffman codes.
+///
+/// * `writer`   - Output bit stream.
+/// * `length`   - Length of the back-reference, in [3:258].
+/// * `distance` - Distance of the back-reference, in [1:32768].
+fn write_match(writer: &mut BitWriter, length: usize, distance: usize) -> () {
+    let index: usize = LENGTH_BASES
+        .iter()
+        .rposition(|&base| base <= length)
+        .unwrap_or(0);
+    write_literal(writer, 257 + index as u32);
+    writer.write((length - LENGTH_BASES[index]) as u32, LENGTH_EXTRAS[index]);
+    let index: usize = DISTANCE_BASES
+        .iter()
+        .rposition(|&base| base <= distance)
+        .unwrap_or(0);
+    writer.write_code(index as u32, 5);
+    writer.write(
+        (distance - DISTANCE_BASES[index]) as u32,
+        DISTANCE_EXTRAS[index],
+    );
+}
+
+/// Computes the Adler-32 checksum.
+///
+/// * `data` - Raw bytes.
+fn adler32(data: &[u8]) -> u32 {
+    const MODULO: u32 = 65521;
+    let mut a: u32 = 1;
+    let mut b: u32 = 0;
+    // the sums do not overflow in a chunk
+    for chunk in data.chunks(5552) {
+        for byte in chunk.iter() {
+            a += *byte as u32;
+            b += a;
+        }
+        a %= MODULO;
+        b %= MODULO;
+    }
+    return (b << 16) | a;
+}
+
+/// Unpacks bits from bytes from the least significant bit, which is used to check the round trip.
+#[cfg(test)]
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+#[cfg(test)]
+impl BitReader<'_> {
+    /// Reads `nbits` bits, the least significant bit first.
+    /// `u32::MAX` skips the rest of the current byte.
+    fn read(&mut self, nbits: u32) -> Result<u32, String> {
+        if u32::MAX == nbits {
+            self.position = self.position.next_multiple_of(8);
+            return Ok(0);
+        }
+        let mut bits: u32 = 0;
+        for m in 0..nbits {
+            let byte: u8 = match self.bytes.get(self.position / 8) {
+                Some(byte) => *byte,
+                None => return Err(String::from("unexpected end of stream")),
+            };
+            bits |= ((byte >> (self.position % 8)) as u32 & 1) << m;
+            self.position += 1;
+        }
+        return Ok(bits);
+    }
+}
+
+/// Decompresses a zlib stream consisting of stored and fixed-Huffman blocks,
+/// which is used to check the round trip.
+///
+/// * `stream` - zlib stream.
+#[cfg(test)]
+pub fn decompress(stream: &[u8]) -> Result<Vec<u8>, String> {
+    if stream.len() < 6 {
+        return Err(String::from("invalid header"));
+    }
+    // the first two bytes form a multiple of 31
+    let check: u32 = (256 * stream[0] as u32 + stream[1] as u32) % 31;
+    if 0 != check {
+        return Err(String::from("invalid header"));
+    }
+    let mut reader: BitReader = BitReader {
+        bytes: &stream[2..stream.len() - 4],
+        position: 0,
+    };
+    let mut read = |nbits: u32| -> Result<u32, String> {
+        return reader.read(nbits);
+    };
+    let mut data: Vec<u8> = Vec::new();
+    loop {
+        let is_final: u32 = read(1)?;
+        match read(2)? {
+            0 => {
+                // stored block: skip to the byte boundary
+                read(u32::MAX)?;
+                let length: u32 = read(16)?;
+                read(16)?;
+                for _ in 0..length {
+                    data.push(read(8)? as u8);
+                }
+            }
+            1 => loop {
+                // decode a fixed Huffman code, stored from the most significant bit
+                let mut code: u32 = 0;
+                let mut symbol: Option<u32> = None;
+                for nbits in 1..=9 {
+                    code = (code << 1) | read(1)?;
+                    symbol = match (nbits, code) {
+                        (7, 0..=23) => Some(256 + code),
+                        (8, 0x30..=0xbf) => Some(code - 0x30),
+                        (8, 0xc0..=0xc7) => Some(280 + code - 0xc0),
+                        (9, 0x190..=0x1ff) => Some(144 + code - 0x190),
+                        _ => None,
+                    };
+                    if symbol.is_some() {
+                        break;
+                    }
+                }
+                let symbol: usize = match symbol {
+                    Some(symbol) => symbol as usize,
+                    None => return Err(String::from("invalid code")),
+                };
+                if symbol < 256 {
+                    data.push(symbol as u8);
+                    continue;
+                }
+                if 256 == symbol {
+                    break;
+                }
+                let length: usize =
+                    LENGTH_BASES[symbol - 257] + read(LENGTH_EXTRAS[symbol - 257])? as usize;
+                let index: usize = read(5)?.reverse_bits() as usize >> 27;
+                let distance: usize =
+                    DISTANCE_BASES[index] + read(DISTANCE_EXTRAS[index])? as usize;
+                if data.len() < distance {
+                    return Err(String::from("invalid distance"));
+                }
+                for _ in 0..length {
+                    data.push(data[data.len() - distance]);
+                }
+            },
+            _ => return Err(String::from("unsupported block type")),
+        };
+        if 1 == is_final {
+            break;
+        }
+    }
+    let checksum: [u8; 4] = [
+        stream[stream.len() - 4],
+        stream[stream.len() - 3],
+        stream[stream.len() - 2],
+        stream[stream.len() - 1],
+    ];
+    if adler32(&data) != u32::from_be_bytes(checksum) {
+        return Err(String::from("checksum mismatch"));
+    }
+    return Ok(data);
+}
+
+#[cfg(test)]
+mod test_zlib {
+    use crate::output::zlib::{adler32, compress, decompress};
+    #[test]
+    fn test_adler32() -> () {
+        assert_eq!(0x11e60398, adler32(b"Wikipedia"));
+    }
+    #[test]
+    fn test_round_trip() -> () {
+        let empty: Vec<u8> = Vec::new();
+        assert_eq!(empty, decompress(&compress(&empty)).unwrap());
+        let text: &[u8] = b"abracadabra, abracadabra, abracadabra";
+        assert_eq!(text, decompress(&compress(text)).unwrap());
+        // long runs and all byte values
+        let data: Vec<u8> = (0..100000)
+            .map(|n: usize| (n / 1000 * 7 + n % 3) as u8)
+            .collect();
+        let stream: Vec<u8> = compress(&data);
+        assert!(stream.len() < data.len() / 10);
+        assert_eq!(data, decompress(&stream).unwrap());
+    }
+}
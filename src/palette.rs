@@ -0,0 +1,395 @@
+#![deny(missing_docs)]
+
+//! Maps the normalised number of iterations to colours.
+//!
+//! A palette is either the radial rainbow, whose hue rotates around the image center,
+//! or a gradient consisting of segments between colour stops.
+//! The position on a gradient is given by `palette_repeat * val + palette_offset`,
+//! which wraps around in the `cyclic` mode and is clipped to [0:1] in the `clamped` mode,
+//! while the interior of the set is painted black.
+//! The colours between the stops are interpolated in the colour space given by `colour_space` (see [`space`]).
+//!
+//! In addition to the built-in palettes ([`NAMES`]), gradients can be loaded from files following the suffixes:
+//!
+//! * `.ggr`: GIMP gradient (see [`ggr`]).
+//! * `.map`: Fractint colour map (see [`fractint`]).
+//! * `.csv`: colour stops (see [`csv`]).
+
+mod csv;
+mod fractint;
+mod ggr;
+mod space;
+
+pub use space::simulate;
+
+use crate::error::{Error, Malformed};
+use crate::options::{ColourSpace, Options, PaletteMode};
+
+/// Names of the built-in palettes.
+pub const NAMES: [&str; 9] = [
+    "rainbow", "grey", "fire", "ocean", "ultra", "viridis", "magma", "inferno", "cividis",
+];
+
+/// Colour stops: position in [0:1] and RGB in [0:255].
+type Stops = &'static [(f64, [u8; 3])];
+
+/// Converts the contents of a palette file to segments.
+type Parser = fn(&str) -> Result<Vec<Segment>, Malformed>;
+
+/// Built-in gradients: name and colour stops.
+///
+/// `viridis`, `magma`, `inferno`, and `cividis` are sampled from those of [matplotlib](https://matplotlib.org/),
+/// whose lightness increases monotonically;
+/// `cividis` is also designed to look alike under the colour vision deficiencies.
+const GRADIENTS: [(&str, Stops); 8] = [
+    ("grey", &[(0., [0, 0, 0]), (1., [255, 255, 255])]),
+    (
+        "fire",
+        &[
+            (0., [0, 0, 0]),
+            (0.33, [200, 30, 0]),
+            (0.66, [255, 170, 0]),
+            (1., [255, 255, 200]),
+        ],
+    ),
+    (
+        "ocean",
+        &[
+            (0., [0, 7, 40]),
+            (0.5, [0, 120, 190]),
+            (1., [220, 250, 255]),
+        ],
+    ),
+    (
+        "ultra",
+        &[
+            (0., [0, 7, 100]),
+            (0.16, [32, 107, 203]),
+            (0.42, [237, 255, 255]),
+            (0.6425, [255, 170, 0]),
+            (0.8575, [0, 2, 0]),
+            (1., [0, 7, 100]),
+        ],
+    ),
+    (
+        "viridis",
+        &[
+            (0., [68, 1, 84]),
+            (0.125, [71, 44, 122]),
+            (0.25, [59, 82, 139]),
+            (0.375, [44, 114, 142]),
+            (0.5, [33, 145, 140]),
+            (0.625, [40, 174, 128]),
+            (0.75, [94, 201, 98]),
+            (0.875, [173, 220, 48]),
+            (1., [253, 231, 37]),
+        ],
+    ),
+    (
+        "magma",
+        &[
+            (0., [0, 0, 4]),
+            (0.125, [28, 16, 68]),
+            (0.25, [79, 18, 123]),
+            (0.375, [129, 37, 129]),
+            (0.5, [181, 54, 122]),
+            (0.625, [229, 80, 100]),
+            (0.75, [251, 135, 97]),
+            (0.875, [254, 194, 135]),
+            (1., [252, 253, 191]),
+        ],
+    ),
+    (
+        "inferno",
+        &[
+            (0., [0, 0, 4]),
+            (0.125, [31, 12, 72]),
+            (0.25, [85, 15, 109]),
+            (0.375, [136, 34, 106]),
+            (0.5, [186, 54, 85]),
+            (0.625, [227, 89, 51]),
+            (0.75, [249, 140, 10]),
+            (0.875, [249, 201, 50]),
+            (1., [252, 255, 164]),
+        ],
+    ),
+    (
+        "cividis",
+        &[
+            (0., [0, 34, 78]),
+            (0.25, [57, 72, 108]),
+            (0.5, [124, 123, 120]),
+            (0.75, [188, 175, 111]),
+            (1., [254, 232, 56]),
+        ],
+    ),
+];
+
+/// Interpolations inside a segment, following those of GIMP gradients.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Blend {
+    /// piecewise linear, passing the mid colour at the middle
+    Linear,
+    /// power law, passing the mid colour at the middle
+    Curved,
+    /// sinusoidal easing of the linear one
+    Sine,
+    /// quarter circle increasing quickly at the left
+    SphereIncreasing,
+    /// quarter circle increasing quickly at the right
+    SphereDecreasing,
+    /// jump at the middle
+    Step,
+}
+
+/// Stores a part of a gradient.
+#[derive(Clone, Debug)]
+pub struct Segment {
+    /// positions of the left end, the middle, and the right end in [0:1]
+    pub positions: [f64; 3],
+    /// RGB values in [0:1] at the left and the right ends
+    pub colours: [[f64; 3]; 2],
+    /// interpolation between the ends
+    pub blend: Blend,
+}
+
+/// Stores a palette.
+pub enum Palette {
+    /// hue rotating around the image center, brightened by the number of iterations
+    Rainbow,
+    /// segments sorted by their positions
+    Gradient(Vec<Segment>),
+}
+
+impl Palette {
+    /// Gives the colour of a pixel.
+    ///
+    /// * `options`     - Fixed parameters: the mode, the offset, and the repeat count of the palette.
+    /// * `val`         - Normalised number of iterations in [0:1].
+    /// * `angle`       - Polar angle of the pixel around the image center.
+    /// * `is_diverged` - Whether the pixel is outside the set.
+    pub fn colour(&self, options: &Options, val: f64, angle: f64, is_diverged: bool) -> [f64; 3] {
+        const PI: f64 = std::f64::consts::PI;
+        let segments: &Vec<Segment> = match self {
+            Palette::Rainbow => {
+                let theta: f64 = options.palette_repeat * angle + 2. * PI * options.palette_offset;
+                return rainbow(theta, val);
+            }
+            Palette::Gradient(segments) => segments,
+        };
+        if !is_diverged {
+            return [0.; 3];
+        }
+        let position: f64 = options.palette_repeat * val + options.palette_offset;
+        let position: f64 = match options.palette_mode {
+            // the upper end of each cycle keeps the end colour,
+            //   so that the maximum is not wrapped around to the start colour
+            PaletteMode::Cyclic if 0. < position && position == position.floor() => 1.,
+            PaletteMode::Cyclic => position - position.floor(),
+            PaletteMode::Clamped => position.clamp(0., 1.),
+        };
+        return evaluate(segments, position, &options.colour_space);
+    }
+}
+
+/// Finds a palette by its name, or loads it from a file when the name ends with a known suffix.
+///
+/// * `name` - Name of a built-in palette or a palette file.
+pub fn load(name: &str) -> Result<Palette, Error> {
+    let parse: Option<Parser> = if name.ends_with(".ggr") {
+        Some(ggr::parse)
+    } else if name.ends_with(".map") {
+        Some(fractint::parse)
+    } else if name.ends_with(".csv") {
+        Some(csv::parse)
+    } else {
+        None
+    };
+    let parse: Parser = match parse {
+        Some(parse) => parse,
+        None => {
+            return match builtin(name) {
+                Some(palette) => Ok(palette),
+                None => Err(Error::Argument(format!(
+                    "palette {} is not found, choose from {} or give a .ggr, .map, or .csv file",
+                    name,
+                    NAMES.join(", ")
+                ))),
+            };
+        }
+    };
+    let contents: String = match std::fs::read_to_string(name) {
+        Ok(contents) => contents,
+        Err(error) => return Err(Error::Read(String::from(name), error)),
+    };
+    return match parse(&contents) {
+        Ok(segments) => Ok(Palette::Gradient(segments)),
+        Err(msg) => Err(Error::Parse(String::from(name), msg)),
+    };
+}
+
+/// Gives a built-in palette.
+///
+/// * `name` - Name of the palette.
+fn builtin(name: &str) -> Option<Palette> {
+    if "rainbow" == name {
+        return Some(Palette::Rainbow);
+    }
+    let (_, stops) = GRADIENTS.iter().find(|(key, _)| *key == name)?;
+    let stops: Vec<(f64, [f64; 3])> = stops
+        .iter()
+        .map(|(position, rgb)| (*position, rgb.map(|value| value as f64 / 255.)))
+        .collect();
+    return Some(Palette::Gradient(from_stops(&stops)));
+}
+
+/// Connects colour stops by linear segments.
+///
+/// * `stops` - Positions in [0:1] and RGB values in [0:1], sorted by the positions.
+pub fn from_stops(stops: &[(f64, [f64; 3])]) -> Vec<Segment> {
+    return stops
+        .windows(2)
+        .map(|pair| {
+            let (left, right) = (pair[0], pair[1]);
+            Segment {
+                positions: [left.0, 0.5 * (left.0 + right.0), right.0],
+                colours: [left.1, right.1],
+                blend: Blend::Linear,
+            }
+        })
+        .collect();
+}
+
+/// Gives the colour at a position of a gradient, which is extended by the end colours.
+///
+/// * `segments` - Segments sorted by their positions.
+/// * `position` - Position in [0:1].
+/// * `space`    - Colour space in which the interpolation is done.
+fn evaluate(segments: &[Segment], position: f64, space: &ColourSpace) -> [f64; 3] {
+    let segment: &Segment = match segments
+        .iter()
+        .find(|segment| position <= segment.positions[2])
+    {
+        Some(segment) => segment,
+        None => match segments.last() {
+            Some(segment) => segment,
+            None => return [0.; 3],
+        },
+    };
+    let [left, middle, right] = segment.positions;
+    let width: f64 = right - left;
+    if width <= 0. {
+        return segment.colours[1];
+    }
+    let pos: f64 = ((position - left) / width).clamp(0., 1.);
+    let mid: f64 = ((middle - left) / width).clamp(1e-12, 1. - 1e-12);
+    let linear: f64 = if pos <= mid {
+        0.5 * pos / mid
+    } else {
+        0.5 + 0.5 * (pos - mid) / (1. - mid)
+    };
+    let factor: f64 = match segment.blend {
+        Blend::Linear => linear,
+        Blend::Curved => pos.powf(0.5f64.ln() / mid.ln()),
+        Blend::Sine => 0.5 * (1. + f64::sin(std::f64::consts::PI * (linear - 0.5))),
+        Blend::SphereIncreasing => f64::sqrt(1. - (linear - 1.).powi(2)),
+        Blend::SphereDecreasing => 1. - f64::sqrt(1. - linear.powi(2)),
+        Blend::Step => {
+            if pos < mid {
+                0.
+            } else {
+                1.
+            }
+        }
+    };
+    let [c0, c1] = segment.colours;
+    return space::mix(c0, c1, factor, space);
+}
+
+/// Maps a scalar value to an RGB pair
+///
+/// * `theta` - Random phase angle.
+/// * `val`   - Scalar value.
+fn rainbow(theta: f64, val: f64) -> [f64; 3] {
+    const PI: f64 = std::f64::consts::PI;
+    return [
+        0.5f64 * val * (1. + f64::sin(0. / 3. * PI + theta)),
+        0.5f64 * val * (1. + f64::sin(2. / 3. * PI + theta)),
+        0.5f64 * val * (1. + f64::sin(4. / 3. * PI + theta)),
+    ];
+}
+
+#[cfg(test)]
+mod test_palette {
+    use crate::options::{ColourSpace, Options};
+    use crate::palette::{builtin, evaluate, from_stops, Blend, Palette, Segment, NAMES};
+    use crate::test_util::assert_close;
+    #[test]
+    fn test_builtin() -> () {
+        for name in NAMES.iter() {
+            assert!(builtin(name).is_some());
+        }
+        assert!(builtin("unknown").is_none());
+    }
+    #[test]
+    fn test_stops() -> () {
+        let segments: Vec<Segment> =
+            from_stops(&[(0., [0., 0., 0.]), (0.25, [1., 0., 0.]), (1., [1., 1., 1.])]);
+        assert_eq!(2, segments.len());
+        assert_close(
+            [0.5, 0., 0.],
+            evaluate(&segments, 0.125, &ColourSpace::Srgb),
+            1e-12,
+        );
+        assert_close(
+            [1., 0., 0.],
+            evaluate(&segments, 0.25, &ColourSpace::Srgb),
+            1e-12,
+        );
+        assert_close(
+            [1., 0.5, 0.5],
+            evaluate(&segments, 0.625, &ColourSpace::Srgb),
+            1e-12,
+        );
+        assert_close(
+            [1., 1., 1.],
+            evaluate(&segments, 1., &ColourSpace::Srgb),
+            1e-12,
+        );
+    }
+    #[test]
+    fn test_blend() -> () {
+        let mut segments: Vec<Segment> = vec![Segment {
+            positions: [0., 0.25, 1.],
+            colours: [[0.; 3], [1.; 3]],
+            blend: Blend::Linear,
+        }];
+        // the middle gives the mid colour
+        for blend in [Blend::Linear, Blend::Curved, Blend::Sine] {
+            segments[0].blend = blend;
+            assert_close(
+                [0.5; 3],
+                evaluate(&segments, 0.25, &ColourSpace::Srgb),
+                1e-12,
+            );
+            assert_close([0.; 3], evaluate(&segments, 0., &ColourSpace::Srgb), 1e-12);
+            assert_close([1.; 3], evaluate(&segments, 1., &ColourSpace::Srgb), 1e-12);
+        }
+        segments[0].blend = Blend::Step;
+        assert_close([0.; 3], evaluate(&segments, 0.2, &ColourSpace::Srgb), 1e-12);
+        assert_close([1.; 3], evaluate(&segments, 0.3, &ColourSpace::Srgb), 1e-12);
+    }
+    #[test]
+    fn test_cyclic() -> () {
+        let palette: Palette =
+            Palette::Gradient(from_stops(&[(0., [0., 0., 0.]), (1., [1., 1., 1.])]));
+        let mut options: Options = Options::default();
+        // the maximum gets the end colour, not the start one
+        assert_close([0.; 3], palette.colour(&options, 0., 0., true), 1e-12);
+        assert_close([1.; 3], palette.colour(&options, 1., 0., true), 1e-12);
+        // the next cycle starts over
+        options.palette_repeat = 2.;
+        assert_close([1.; 3], palette.colour(&options, 0.5, 0., true), 1e-12);
+        assert_close([0.5; 3], palette.colour(&options, 0.75, 0., true), 1e-12);
+    }
+}
@@ -0,0 +1,92 @@
+#![deny(missing_docs)]
+
+//! Reads colour stops from a CSV file (`.csv`).
+//!
+//! ```text
+//! position,r,g,b
+//! 0,0,0,0
+//! 0.4,255,128,0
+//! 1,255,255,255
+//! ```
+//!
+//! Each line gives a position in [0:1] and an RGB value in [0:255], sorted by the positions.
+//! Lines starting with `#` and a header line are ignored.
+
+use crate::error::Malformed;
+use crate::palette::{self, Segment};
+
+/// Converts the contents of a CSV file to segments.
+///
+/// * `contents` - Whole text of the CSV file.
+pub fn parse(contents: &str) -> Result<Vec<Segment>, Malformed> {
+    let mut stops: Vec<(f64, [f64; 3])> = Vec::new();
+    for (n, line) in contents.lines().enumerate() {
+        let line: &str = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let words: Vec<&str> = line.split(',').map(|word| word.trim()).collect();
+        // the header begins with a name
+        if 0 == n && words[0].starts_with(|c: char| c.is_ascii_alphabetic()) {
+            continue;
+        }
+        if 4 != words.len() {
+            return Err(format!("line {}: expect 4 columns", n + 1).into());
+        }
+        let mut values: [f64; 4] = [0.; 4];
+        for m in 0..4 {
+            values[m] = match words[m].parse::<f64>() {
+                Ok(value) => value,
+                Err(error) => {
+                    return Err(Malformed::caused(
+                        format!("line {}: invalid value {}", n + 1, words[m]),
+                        error,
+                    ))
+                }
+            };
+        }
+        if !(0. <= values[0] && values[0] <= 1.) {
+            return Err(format!("line {}: position should be in [0:1]", n + 1).into());
+        }
+        if values[1..4]
+            .iter()
+            .any(|value| !(0. <= *value && *value <= 255.))
+        {
+            return Err(format!("line {}: colour should be in [0:255]", n + 1).into());
+        }
+        if let Some((position, _)) = stops.last() {
+            if values[0] < *position {
+                return Err(format!("line {}: positions should be sorted", n + 1).into());
+            }
+        }
+        stops.push((
+            values[0],
+            [values[1] / 255., values[2] / 255., values[3] / 255.],
+        ));
+    }
+    if stops.len() < 2 {
+        return Err(String::from("expect at least 2 stops").into());
+    }
+    return Ok(palette::from_stops(&stops));
+}
+
+#[cfg(test)]
+mod test_csv {
+    use crate::palette::csv::parse;
+    use crate::palette::Segment;
+    #[test]
+    fn test_parse0() -> () {
+        let segments: Vec<Segment> =
+            parse("position,r,g,b\n# comment\n0,0,0,0\n0.4, 255, 0, 0\n1,255,255,255\n").unwrap();
+        assert_eq!(2, segments.len());
+        assert_eq!([0., 0.2, 0.4], segments[0].positions);
+        assert_eq!([[0., 0., 0.], [1., 0., 0.]], segments[0].colours);
+    }
+    #[test]
+    fn test_parse1() -> () {
+        assert!(parse("0,0,0,0\n").is_err());
+        assert!(parse("0,0,0,0\n1,0,0\n").is_err());
+        assert!(parse("0.5,0,0,0\n0.2,0,0,0\n").is_err());
+        assert!(parse("0,0,0,0\n1,0,0,256\n").is_err());
+    }
+}
@@ -0,0 +1,72 @@
+#![deny(missing_docs)]
+
+//! Reads a Fractint colour map (`.map`).
+//!
+//! ```text
+//! 0 0 0      black
+//! 255 128 0  orange
+//! ```
+//!
+//! Each line gives an RGB value in [0:255] followed by an optional comment,
+//! and the colours are placed at the same intervals from the first to the last.
+
+use crate::error::Malformed;
+use crate::palette::{self, Segment};
+
+/// Converts the contents of a Fractint colour map to segments.
+///
+/// * `contents` - Whole text of the colour map.
+pub fn parse(contents: &str) -> Result<Vec<Segment>, Malformed> {
+    let mut colours: Vec<[f64; 3]> = Vec::new();
+    for (n, line) in contents.lines().enumerate() {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        if words.is_empty() {
+            continue;
+        }
+        if words.len() < 3 {
+            return Err(format!("line {}: expect 3 columns", n + 1).into());
+        }
+        let mut rgb: [f64; 3] = [0.; 3];
+        for m in 0..3 {
+            rgb[m] = match words[m].parse::<u8>() {
+                Ok(value) => value as f64 / 255.,
+                Err(error) => {
+                    return Err(Malformed::caused(
+                        format!("line {}: invalid value {}", n + 1, words[m]),
+                        error,
+                    ))
+                }
+            };
+        }
+        colours.push(rgb);
+    }
+    if colours.len() < 2 {
+        return Err(String::from("expect at least 2 colours").into());
+    }
+    let nitems: usize = colours.len();
+    let stops: Vec<(f64, [f64; 3])> = colours
+        .into_iter()
+        .enumerate()
+        .map(|(n, rgb)| (n as f64 / (nitems - 1) as f64, rgb))
+        .collect();
+    return Ok(palette::from_stops(&stops));
+}
+
+#[cfg(test)]
+mod test_fractint {
+    use crate::palette::fractint::parse;
+    use crate::palette::Segment;
+    #[test]
+    fn test_parse0() -> () {
+        let segments: Vec<Segment> = parse("0 0 0 black\n\n255 0 0\n255 255 255 white\n").unwrap();
+        assert_eq!(2, segments.len());
+        assert_eq!([0., 0.25, 0.5], segments[0].positions);
+        assert_eq!([[1., 0., 0.], [1., 1., 1.]], segments[1].colours);
+    }
+    #[test]
+    fn test_parse1() -> () {
+        assert!(parse("0 0 0\n").is_err());
+        assert!(parse("0 0 0\n0 256 0\n").is_err());
+        assert!(parse("0 0 0\n0 0\n").is_err());
+    }
+}
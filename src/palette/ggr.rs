@@ -0,0 +1,123 @@
+#![deny(missing_docs)]
+
+//! Reads a GIMP gradient (`.ggr`).
+//!
+//! ```text
+//! GIMP Gradient
+//! Name: Sunrise
+//! 2
+//! 0.0 0.25 0.5  0 0 0 1  1 0 0 1  0 0
+//! 0.5 0.75 1.0  1 0 0 1  1 1 0 1  1 0
+//! ```
+//!
+//! After the header and the number of segments, each line gives a segment:
+//! the left, middle, and right positions, the RGBA values at both ends,
+//! the blending function, and the colouring type, optionally followed by two flags of the end colours.
+//! The alpha values are ignored, and the segments blended in HSV are blended in RGB instead.
+
+use crate::error::Malformed;
+use crate::palette::{Blend, Segment};
+
+/// Converts the contents of a GIMP gradient to segments.
+///
+/// * `contents` - Whole text of the gradient file.
+pub fn parse(contents: &str) -> Result<Vec<Segment>, Malformed> {
+    let mut lines = contents.lines().enumerate();
+    match lines.next() {
+        Some((_, line)) if "GIMP Gradient" == line.trim() => {}
+        _ => return Err(String::from("line 1: expect GIMP Gradient").into()),
+    };
+    let mut nsegments: Option<usize> = None;
+    let mut segments: Vec<Segment> = Vec::new();
+    for (n, line) in lines {
+        let line: &str = line.trim();
+        if line.is_empty() || line.starts_with("Name:") {
+            continue;
+        }
+        if nsegments.is_none() {
+            nsegments = match line.parse::<usize>() {
+                Ok(nsegments) => Some(nsegments),
+                Err(error) => {
+                    return Err(Malformed::caused(
+                        format!("line {}: expect the number of segments", n + 1),
+                        error,
+                    ))
+                }
+            };
+            continue;
+        }
+        let words: Vec<&str> = line.split_whitespace().collect();
+        if words.len() < 13 {
+            return Err(format!("line {}: expect at least 13 columns", n + 1).into());
+        }
+        let mut values: [f64; 11] = [0.; 11];
+        for m in 0..11 {
+            values[m] = match words[m].parse::<f64>() {
+                Ok(value) => value,
+                Err(error) => {
+                    return Err(Malformed::caused(
+                        format!("line {}: invalid value {}", n + 1, words[m]),
+                        error,
+                    ))
+                }
+            };
+        }
+        let blend: Blend = match words[11] {
+            "0" => Blend::Linear,
+            "1" => Blend::Curved,
+            "2" => Blend::Sine,
+            "3" => Blend::SphereIncreasing,
+            "4" => Blend::SphereDecreasing,
+            "5" => Blend::Step,
+            _ => {
+                return Err(
+                    format!("line {}: unknown blending function {}", n + 1, words[11]).into(),
+                )
+            }
+        };
+        let positions: [f64; 3] = [values[0], values[1], values[2]];
+        if !(0. <= positions[0] && positions[0] <= positions[1] && positions[1] <= positions[2]) {
+            return Err(format!("line {}: positions should be sorted", n + 1).into());
+        }
+        segments.push(Segment {
+            positions,
+            colours: [
+                [values[3], values[4], values[5]],
+                [values[7], values[8], values[9]],
+            ],
+            blend,
+        });
+    }
+    return match nsegments {
+        Some(nsegments) if 0 < nsegments && nsegments == segments.len() => Ok(segments),
+        Some(nsegments) => {
+            Err(format!("expect {} segments, found {}", nsegments, segments.len()).into())
+        }
+        None => Err(String::from("no segment is found").into()),
+    };
+}
+
+#[cfg(test)]
+mod test_ggr {
+    use crate::palette::ggr::parse;
+    use crate::palette::{Blend, Segment};
+    #[test]
+    fn test_parse0() -> () {
+        let segments: Vec<Segment> = parse(
+            "GIMP Gradient\nName: Sunrise\n2\n0.0 0.25 0.5 0 0 0 1 1 0 0 1 0 0\n0.5 0.75 1.0 1 0 0 1 1 1 0 1 1 0 0 0\n",
+        )
+        .unwrap();
+        assert_eq!(2, segments.len());
+        assert_eq!([0., 0.25, 0.5], segments[0].positions);
+        assert_eq!([[1., 0., 0.], [1., 1., 0.]], segments[1].colours);
+        assert_eq!(Blend::Curved, segments[1].blend);
+    }
+    #[test]
+    fn test_parse1() -> () {
+        assert!(parse("Gradient\n1\n0 0.5 1 0 0 0 1 1 1 1 1 0 0\n").is_err());
+        // wrong number of segments
+        assert!(parse("GIMP Gradient\n2\n0 0.5 1 0 0 0 1 1 1 1 1 0 0\n").is_err());
+        // unknown blending function
+        assert!(parse("GIMP Gradient\n1\n0 0.5 1 0 0 0 1 1 1 1 1 9 0\n").is_err());
+    }
+}
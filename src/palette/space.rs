@@ -0,0 +1,197 @@
+#![deny(missing_docs)]
+
+//! Converts colours between the gamma-encoded sRGB and the other colour spaces.
+//!
+//! * linear sRGB, in which the light intensities are additive.
+//! * [OKLab](https://bottosson.github.io/posts/oklab/), in which the Euclidean distance approximates the perceived difference,
+//!   and its polar form OKLCh (lightness, chroma, and hue).
+//!
+//! In addition, the colour vision deficiencies are simulated following
+//! [Machado et al. (2009)](https://doi.org/10.1109/TVCG.2009.113) with the full severity.
+
+use crate::options::{ColourSpace, Deficiency};
+
+/// Matrices simulating the deficiencies in the linear sRGB.
+const PROTANOPIA: [[f64; 3]; 3] = [
+    [0.152_286, 1.052_583, -0.204_868],
+    [0.114_503, 0.786_281, 0.099_216],
+    [-0.003_882, -0.048_116, 1.051_998],
+];
+const DEUTERANOPIA: [[f64; 3]; 3] = [
+    [0.367_322, 0.860_646, -0.227_968],
+    [0.280_085, 0.672_501, 0.047_413],
+    [-0.011_820, 0.042_940, 0.968_881],
+];
+const TRITANOPIA: [[f64; 3]; 3] = [
+    [1.255_528, -0.076_749, -0.178_779],
+    [-0.078_411, 0.930_809, 0.147_602],
+    [0.004_733, 0.691_367, 0.303_900],
+];
+
+/// Interpolates two sRGB colours in the given colour space.
+///
+/// * `c0`     - sRGB values in [0:1] at `factor = 0`.
+/// * `c1`     - sRGB values in [0:1] at `factor = 1`.
+/// * `factor` - Weight of `c1` in [0:1].
+/// * `space`  - Colour space in which the interpolation is done.
+pub fn mix(c0: [f64; 3], c1: [f64; 3], factor: f64, space: &ColourSpace) -> [f64; 3] {
+    let lerp = |v0: [f64; 3], v1: [f64; 3]| -> [f64; 3] {
+        return [0, 1, 2].map(|m| v0[m] + factor * (v1[m] - v0[m]));
+    };
+    return match space {
+        ColourSpace::Srgb => lerp(c0, c1),
+        ColourSpace::Linear => to_srgb(lerp(to_linear(c0), to_linear(c1))),
+        ColourSpace::Oklab => from_oklab(lerp(to_oklab(c0), to_oklab(c1))),
+        ColourSpace::Oklch => {
+            let [l0, chroma0, h0] = to_lch(to_oklab(c0));
+            let [l1, chroma1, h1] = to_lch(to_oklab(c1));
+            // hue along the shorter arc, which is irrelevant for greys
+            let mut dh: f64 = h1 - h0;
+            dh -= 2. * std::f64::consts::PI * (dh / (2. * std::f64::consts::PI)).round();
+            let (h0, dh) = if chroma0 < 1e-6 {
+                (h1, 0.)
+            } else if chroma1 < 1e-6 {
+                (h0, 0.)
+            } else {
+                (h0, dh)
+            };
+            from_oklab(from_lch([
+                l0 + factor * (l1 - l0),
+                chroma0 + factor * (chroma1 - chroma0),
+                h0 + factor * dh,
+            ]))
+        }
+    };
+}
+
+/// Gives the colour perceived under a colour vision deficiency.
+///
+/// * `rgb`        - sRGB values in [0:1].
+/// * `deficiency` - Kind of the deficiency.
+pub fn simulate(rgb: [f64; 3], deficiency: &Deficiency) -> [f64; 3] {
+    let matrix: &[[f64; 3]; 3] = match deficiency {
+        Deficiency::Protanopia => &PROTANOPIA,
+        Deficiency::Deuteranopia => &DEUTERANOPIA,
+        Deficiency::Tritanopia => &TRITANOPIA,
+    };
+    let rgb: [f64; 3] = to_linear(rgb);
+    let rgb: [f64; 3] = matrix.map(|row| row[0] * rgb[0] + row[1] * rgb[1] + row[2] * rgb[2]);
+    return to_srgb(rgb.map(|value| value.clamp(0., 1.)));
+}
+
+/// Removes the gamma of sRGB values.
+///
+/// * `rgb` - sRGB values in [0:1].
+fn to_linear(rgb: [f64; 3]) -> [f64; 3] {
+    return rgb.map(|value| {
+        if value <= 0.04045 {
+            value / 12.92
+        } else {
+            ((value + 0.055) / 1.055).powf(2.4)
+        }
+    });
+}
+
+/// Applies the gamma of sRGB to linear values.
+///
+/// * `rgb` - Linear sRGB values in [0:1].
+fn to_srgb(rgb: [f64; 3]) -> [f64; 3] {
+    return rgb.map(|value| {
+        if value <= 0.003_130_8 {
+            12.92 * value
+        } else {
+            1.055 * value.powf(1. / 2.4) - 0.055
+        }
+    });
+}
+
+/// Converts sRGB values to OKLab.
+///
+/// * `rgb` - sRGB values in [0:1].
+fn to_oklab(rgb: [f64; 3]) -> [f64; 3] {
+    let [r, g, b] = to_linear(rgb);
+    let l: f64 = (0.412_221_470_8 * r + 0.536_332_536_3 * g + 0.051_445_992_9 * b).cbrt();
+    let m: f64 = (0.211_903_498_2 * r + 0.680_699_545_1 * g + 0.107_396_956_6 * b).cbrt();
+    let s: f64 = (0.088_302_461_9 * r + 0.281_718_837_6 * g + 0.629_978_700_5 * b).cbrt();
+    return [
+        0.210_454_255_3 * l + 0.793_617_785 * m - 0.004_072_046_8 * s,
+        1.977_998_495_1 * l - 2.428_592_205 * m + 0.450_593_709_9 * s,
+        0.025_904_037_1 * l + 0.782_771_766_2 * m - 0.808_675_766 * s,
+    ];
+}
+
+/// Converts OKLab values to sRGB, which are clipped to [0:1].
+///
+/// * `lab` - OKLab values.
+fn from_oklab(lab: [f64; 3]) -> [f64; 3] {
+    let [lightness, a, b] = lab;
+    let l: f64 = (lightness + 0.396_337_777_4 * a + 0.215_803_757_3 * b).powi(3);
+    let m: f64 = (lightness - 0.105_561_345_8 * a - 0.063_854_172_8 * b).powi(3);
+    let s: f64 = (lightness - 0.089_484_177_5 * a - 1.291_485_548 * b).powi(3);
+    let rgb: [f64; 3] = [
+        4.076_741_662_1 * l - 3.307_711_591_3 * m + 0.230_969_929_2 * s,
+        -1.268_438_004_6 * l + 2.609_757_401_1 * m - 0.341_319_396_5 * s,
+        -0.004_196_086_3 * l - 0.703_418_614_7 * m + 1.707_614_701 * s,
+    ];
+    return to_srgb(rgb.map(|value| value.clamp(0., 1.)));
+}
+
+/// Converts OKLab values to the polar form: lightness, chroma, and hue in radians.
+///
+/// * `lab` - OKLab values.
+fn to_lch(lab: [f64; 3]) -> [f64; 3] {
+    return [lab[0], f64::hypot(lab[1], lab[2]), lab[2].atan2(lab[1])];
+}
+
+/// Converts the polar form to OKLab values.
+///
+/// * `lch` - Lightness, chroma, and hue in radians.
+fn from_lch(lch: [f64; 3]) -> [f64; 3] {
+    return [lch[0], lch[1] * lch[2].cos(), lch[1] * lch[2].sin()];
+}
+
+#[cfg(test)]
+mod test_space {
+    use crate::options::{ColourSpace, Deficiency};
+    use crate::palette::space::{from_oklab, mix, simulate, to_linear, to_oklab, to_srgb};
+    use crate::test_util::assert_close;
+    #[test]
+    fn test_round_trip() -> () {
+        for rgb in [[0., 0., 0.], [1., 1., 1.], [0.2, 0.5, 0.9], [1., 0., 0.]] {
+            assert_close(rgb, to_srgb(to_linear(rgb)), 1e-12);
+            assert_close(rgb, from_oklab(to_oklab(rgb)), 1e-6);
+        }
+        // white has unit lightness and no chroma
+        assert_close([1., 0., 0.], to_oklab([1., 1., 1.]), 1e-6);
+    }
+    #[test]
+    fn test_mix() -> () {
+        let (c0, c1): ([f64; 3], [f64; 3]) = ([0.; 3], [1.; 3]);
+        for space in [
+            ColourSpace::Srgb,
+            ColourSpace::Linear,
+            ColourSpace::Oklab,
+            ColourSpace::Oklch,
+        ] {
+            assert_close(c0, mix(c0, c1, 0., &space), 1e-6);
+            assert_close(c1, mix(c0, c1, 1., &space), 1e-6);
+        }
+        // the midpoint of black and white is brighter in the linear space
+        assert!(0.5 < mix(c0, c1, 0.5, &ColourSpace::Linear)[0]);
+    }
+    #[test]
+    fn test_simulate() -> () {
+        // greys are seen as they are
+        for deficiency in [
+            Deficiency::Protanopia,
+            Deficiency::Deuteranopia,
+            Deficiency::Tritanopia,
+        ] {
+            assert_close([0.5; 3], simulate([0.5; 3], &deficiency), 1e-3);
+        }
+        // red and green are seen as similar hues without the long-wavelength cones
+        let red: [f64; 3] = simulate([1., 0., 0.], &Deficiency::Protanopia);
+        let green: [f64; 3] = simulate([0., 1., 0.], &Deficiency::Protanopia);
+        assert!((red[0] / red[1] - green[0] / green[1]).abs() < 0.1);
+    }
+}
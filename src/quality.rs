@@ -0,0 +1,258 @@
+#![deny(missing_docs)]
+
+//! Scores the aesthetic quality of a rendered image.
+//!
+//! Even when a center is found, the final image may be mostly one flat colour or interior.
+//! The scores computed here are compared with the thresholds in [`crate::options::Options`]
+//! to reject such boring results, so that the search can be retried.
+
+use crate::coord::Coord;
+use crate::mandelbrot::Point;
+use crate::options::Options;
+
+/// Minimum difference of the luminance between neighbouring pixels regarded as an edge.
+const EDGE_CONTRAST: f64 = 0.1;
+
+/// Number of bins of the escape-count histogram.
+const NBINS: usize = 64;
+
+/// Stores the quality scores of an image.
+pub struct Scores {
+    /// fraction of the pixels which do not diverge
+    pub interior: f64,
+    /// variance of the colour channels, averaged over the three channels
+    pub colour_variance: f64,
+    /// fraction of the pixels whose luminance differs from the neighbours
+    pub edge_density: f64,
+    /// normalised entropy of the escape-count histogram
+    pub histogram_spread: f64,
+}
+
+/// Tells whether any threshold is tighter than the default, which accepts any image.
+///
+/// * `options` - Fixed parameters containing the thresholds.
+pub fn is_judged(options: &Options) -> bool {
+    return options.max_interior < 1.
+        || 0. < options.min_colour_variance
+        || 0. < options.min_edge_density
+        || 0. < options.min_histogram_spread;
+}
+
+impl Scores {
+    /// Computes the scores of an image.
+    ///
+    /// * `resols` - The number of pixels in two directions.
+    /// * `points` - Result of the recurrence relation for each pixel.
+    /// * `pixels` - RGB values of each pixel.
+    pub fn new(resols: &Coord<usize>, points: &[Point], pixels: &[u8]) -> Scores {
+        return Scores {
+            interior: interior(points),
+            colour_variance: colour_variance(pixels),
+            edge_density: edge_density(resols, pixels),
+            histogram_spread: histogram_spread(points),
+        };
+    }
+
+    /// Lists the scores which do not satisfy the thresholds.
+    ///
+    /// * `options` - Fixed parameters containing the thresholds.
+    pub fn failures(&self, options: &Options) -> Vec<&'static str> {
+        let mut failures: Vec<&'static str> = Vec::new();
+        if options.max_interior < self.interior {
+            failures.push("interior fraction");
+        }
+        if self.colour_variance < options.min_colour_variance {
+            failures.push("colour variance");
+        }
+        if self.edge_density < options.min_edge_density {
+            failures.push("edge density");
+        }
+        if self.histogram_spread < options.min_histogram_spread {
+            failures.push("histogram spread");
+        }
+        return failures;
+    }
+
+    /// Outputs the scores.
+    pub fn print(&self) -> () {
+        eprintln!("quality of the image:");
+        eprintln!("    interior fraction : {:.3}", self.interior);
+        eprintln!("    colour variance   : {:.3e}", self.colour_variance);
+        eprintln!("    edge density      : {:.3}", self.edge_density);
+        eprintln!("    histogram spread  : {:.3}", self.histogram_spread);
+    }
+}
+
+/// Computes the fraction of the points which do not diverge.
+///
+/// * `points` - Result of the recurrence relation for each pixel.
+fn interior(points: &[Point]) -> f64 {
+    // an empty image has nothing but interior
+    if points.is_empty() {
+        return 1.;
+    }
+    let count: usize = points.iter().filter(|point| !point.is_diverged).count();
+    return count as f64 / points.len() as f64;
+}
+
+/// Computes the variance of each colour channel normalised to [0:1], averaged over the channels.
+///
+/// * `pixels` - RGB values of each pixel.
+fn colour_variance(pixels: &[u8]) -> f64 {
+    let nitems: usize = pixels.len() / 3;
+    // an empty image has no variation
+    if 0 == nitems {
+        return 0.;
+    }
+    let mut variance: f64 = 0.;
+    for m in 0..3 {
+        let values = (0..nitems).map(|n| pixels[3 * n + m] as f64 / 255.);
+        let mean: f64 = values.clone().sum::<f64>() / nitems as f64;
+        variance += values.map(|value| (value - mean).powi(2)).sum::<f64>() / nitems as f64;
+    }
+    return variance / 3.;
+}
+
+/// Computes the fraction of the pixels whose luminance differs from the right or the upper neighbour.
+///
+/// An image of a single row or column has no pixel with both neighbours, and is regarded as without edges.
+///
+/// * `resols` - The number of pixels in two directions.
+/// * `pixels` - RGB values of each pixel.
+fn edge_density(resols: &Coord<usize>, pixels: &[u8]) -> f64 {
+    let luminance = |i: usize, j: usize| -> f64 {
+        let n: usize = j * resols.x + i;
+        return (0.299 * pixels[3 * n] as f64
+            + 0.587 * pixels[3 * n + 1] as f64
+            + 0.114 * pixels[3 * n + 2] as f64)
+            / 255.;
+    };
+    if resols.x < 2 || resols.y < 2 {
+        return 0.;
+    }
+    let mut count: usize = 0;
+    for j in 0..resols.y - 1 {
+        for i in 0..resols.x - 1 {
+            let center: f64 = luminance(i, j);
+            if EDGE_CONTRAST < (luminance(i + 1, j) - center).abs()
+                || EDGE_CONTRAST < (luminance(i, j + 1) - center).abs()
+            {
+                count += 1;
+            }
+        }
+    }
+    return count as f64 / ((resols.x - 1) * (resols.y - 1)) as f64;
+}
+
+/// Computes the entropy of the escape-count histogram of the diverging points,
+/// normalised to [0:1] by that of the uniform distribution.
+///
+/// * `points` - Result of the recurrence relation for each pixel.
+fn histogram_spread(points: &[Point]) -> f64 {
+    let iters: Vec<u64> = points
+        .iter()
+        .filter(|point| point.is_diverged)
+        .map(|point| point.iter)
+        .collect();
+    let (min, max): (u64, u64) = match (iters.iter().min(), iters.iter().max()) {
+        (Some(&min), Some(&max)) => (min, max),
+        _ => return 0.,
+    };
+    if min == max {
+        return 0.;
+    }
+    let mut bins: [usize; NBINS] = [0; NBINS];
+    for iter in iters.iter() {
+        let index: usize = ((iter - min) as f64 / (max - min) as f64 * NBINS as f64) as usize;
+        bins[usize::min(index, NBINS - 1)] += 1;
+    }
+    let mut entropy: f64 = 0.;
+    for bin in bins.iter() {
+        if 0 < *bin {
+            let p: f64 = *bin as f64 / iters.len() as f64;
+            entropy -= p * p.ln();
+        }
+    }
+    return entropy / (NBINS as f64).ln();
+}
+
+#[cfg(test)]
+mod test_quality {
+    use crate::coord::Coord;
+    use crate::mandelbrot::Point;
+    use crate::options::Options;
+    use crate::quality::{self, Scores};
+    #[test]
+    fn test_judged() -> () {
+        let options: Options = Options::default();
+        assert!(!quality::is_judged(&options));
+        let options: Options = Options {
+            min_edge_density: 0.1,
+            ..Options::default()
+        };
+        assert!(quality::is_judged(&options));
+    }
+    #[test]
+    fn test_flat() -> () {
+        let resols: Coord<usize> = Coord { x: 4, y: 3 };
+        let points: Vec<Point> = vec![
+            Point {
+                is_diverged: false,
+                iter: 0,
+                smooth: 0.,
+                distance: 0.,
+            };
+            12
+        ];
+        let pixels: Vec<u8> = vec![255u8; 36];
+        let scores: Scores = Scores::new(&resols, &points, &pixels);
+        assert_eq!(1., scores.interior);
+        assert_eq!(0., scores.colour_variance);
+        assert_eq!(0., scores.edge_density);
+        assert_eq!(0., scores.histogram_spread);
+    }
+    #[test]
+    fn test_stripes() -> () {
+        // vertical stripes: every pixel differs from its right neighbour
+        let resols: Coord<usize> = Coord { x: 4, y: 3 };
+        let points: Vec<Point> = (0..12)
+            .map(|n| Point {
+                is_diverged: true,
+                iter: (n % 2) as u64,
+                smooth: (n % 2) as f64,
+                distance: 1.,
+            })
+            .collect();
+        let pixels: Vec<u8> = (0..36).map(|n| [0u8, 255u8][n / 3 % 2]).collect();
+        let scores: Scores = Scores::new(&resols, &points, &pixels);
+        assert_eq!(0., scores.interior);
+        assert!((0.25 - scores.colour_variance).abs() < 1e-12);
+        assert_eq!(1., scores.edge_density);
+        assert!((2f64.ln() / 64f64.ln() - scores.histogram_spread).abs() < 1e-12);
+    }
+    #[test]
+    fn test_degenerate() -> () {
+        // a single row or column gives finite scores, which fail the thresholds instead of passing silently
+        for resols in [
+            Coord { x: 4, y: 1 },
+            Coord { x: 1, y: 4 },
+            Coord { x: 0, y: 0 },
+        ] {
+            let nitems: usize = resols.x * resols.y;
+            let points: Vec<Point> = (0..nitems)
+                .map(|n| Point {
+                    is_diverged: true,
+                    iter: n as u64,
+                    smooth: n as f64,
+                    distance: 1.,
+                })
+                .collect();
+            let pixels: Vec<u8> = (0..3 * nitems).map(|n| [0u8, 255u8][n / 3 % 2]).collect();
+            let scores: Scores = Scores::new(&resols, &points, &pixels);
+            assert!(scores.interior.is_finite());
+            assert!(scores.colour_variance.is_finite());
+            assert!(scores.edge_density < 0.05);
+            assert!(scores.histogram_spread.is_finite());
+        }
+    }
+}
@@ -29,4 +29,12 @@ impl Random {
         let rng: &mut rand::rngs::StdRng = &mut self.rng;
         return rng.gen_range(min..max);
     }
+    /// Returns a random integer which is equal to or larger than `min` and smaller than `max`.
+    ///   
+    /// * `min` - Small limit.
+    /// * `max` - Large limit.
+    pub fn gen_integer(&mut self, min: u64, max: u64) -> u64 {
+        let rng: &mut rand::rngs::StdRng = &mut self.rng;
+        return rng.gen_range(min..max);
+    }
 }
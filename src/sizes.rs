@@ -0,0 +1,99 @@
+#![deny(missing_docs)]
+
+//! Renders the found view at several resolutions, e.g., for the wallpapers of several screens.
+//!
+//! The center is searched and judged only once at `width` and `height`,
+//! and every size is then rendered around the same center, keeping either
+//!
+//! * `view`: the field of view, which is fitted to the aspect ratio of each size so that nothing is cropped, or
+//! * `spacing`: the distance between two adjacent pixels, so that larger screens show more.
+//!
+//! Each image is written to the name with the resolution appended, e.g., `image_1280x800.ppm`,
+//! together with its own manifest.
+
+use crate::coord::Coord;
+use crate::error::Error;
+use crate::mandelbrot::{self, Point};
+use crate::manifest::{self, Manifest};
+use crate::options::{Options, SizeScale};
+use crate::output;
+use std::time::{Duration, Instant};
+
+/// Renders and writes the image of each size.
+///
+/// * `options`     - Configuration parameters of the searched view.
+/// * `center`      - Center of the image shared among the sizes.
+/// * `time_search` - Time spent to find the center, which is recorded in each manifest.
+pub fn execute(options: &Options, center: &Coord<f64>, time_search: Duration) -> Result<(), Error> {
+    for resols in options.sizes.iter() {
+        let mut sized: Options = options.clone();
+        sized.resols = *resols;
+        sized.grid_size = grid_size(
+            &options.size_scale,
+            &options.resols,
+            options.grid_size,
+            resols,
+        );
+        sized.fname =
+            output::suffixed_fname(&options.fname, &format!("{}x{}", resols.x, resols.y))?;
+        // the raw data is kept only for the searched view
+        sized.data = None;
+        // each image is reproduced by its own manifest alone
+        sized.sizes = Vec::new();
+        let timer: Instant = Instant::now();
+        let points: Vec<Point> = mandelbrot::solve_recurrence_relation(&sized, center)?;
+        let time_solve: Duration = timer.elapsed();
+        let timer: Instant = Instant::now();
+        output::execute(&sized, center, points)?;
+        let time_output: Duration = timer.elapsed();
+        eprintln!("{}x{} is written to {}", resols.x, resols.y, sized.fname);
+        let manifest: Manifest = Manifest::new(
+            &sized,
+            center,
+            &[
+                ("search", time_search),
+                ("solve", time_solve),
+                ("output", time_output),
+            ],
+        );
+        manifest.write(&manifest::sidecar_fname(&sized.fname))?;
+    }
+    return Ok(());
+}
+
+/// Computes the grid size of another resolution.
+///
+/// * `scale`     - Quantity kept among the sizes.
+/// * `old`       - Resolution of the searched view.
+/// * `grid_size` - Grid size of the searched view.
+/// * `new`       - Resolution of the image to be rendered.
+fn grid_size(scale: &SizeScale, old: &Coord<usize>, grid_size: f64, new: &Coord<usize>) -> f64 {
+    return match scale {
+        SizeScale::View => f64::max(
+            (old.x as f64 * grid_size) / new.x as f64,
+            (old.y as f64 * grid_size) / new.y as f64,
+        ),
+        SizeScale::Spacing => grid_size,
+    };
+}
+
+#[cfg(test)]
+mod test_sizes {
+    use crate::coord::Coord;
+    use crate::options::SizeScale;
+    use crate::sizes::grid_size;
+    #[test]
+    fn test_grid_size() -> () {
+        let old: Coord<usize> = Coord { x: 1600, y: 900 };
+        // twice as many pixels in each direction halve the grid size
+        let new: Coord<usize> = Coord { x: 3200, y: 1800 };
+        assert_eq!(0.5, grid_size(&SizeScale::View, &old, 1., &new));
+        assert_eq!(1., grid_size(&SizeScale::Spacing, &old, 1., &new));
+        // a taller screen keeps the width and shows more vertically
+        let new: Coord<usize> = Coord { x: 1600, y: 1200 };
+        assert_eq!(1., grid_size(&SizeScale::View, &old, 1., &new));
+        // a wider screen keeps the height and shows more horizontally
+        let new: Coord<usize> = Coord { x: 900, y: 450 };
+        assert_eq!(2., grid_size(&SizeScale::View, &old, 1., &new));
+    }
+}
@@ -0,0 +1,19 @@
+#![deny(missing_docs)]
+
+//! Helpers shared among the unit tests.
+
+/// Asserts that two colours or vectors coincide up to the tolerance in every component.
+///
+/// * `expected` - Expected components.
+/// * `actual`   - Computed components.
+/// * `tol`      - Maximum difference allowed in each component.
+pub fn assert_close(expected: [f64; 3], actual: [f64; 3], tol: f64) -> () {
+    for m in 0..3 {
+        assert!(
+            (expected[m] - actual[m]).abs() < tol,
+            "expect {:?}, got {:?}",
+            expected,
+            actual
+        );
+    }
+}
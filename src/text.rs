@@ -0,0 +1,34 @@
+#![deny(missing_docs)]
+
+//! Reads the plain-text files listing one item per line, i.e., the keyframe, bookmark, history, and layout files.
+//!
+//! Everything after `#` is ignored, and empty lines are skipped.
+
+/// Gives the lines which have contents, trimmed and paired with their indices from zero.
+///
+/// * `contents` - Whole text of the file.
+pub fn lines(contents: &str) -> impl Iterator<Item = (usize, &str)> {
+    return contents.lines().enumerate().filter_map(|(n, line)| {
+        // drop comments
+        let line: &str = match line.split('#').next() {
+            Some(line) => line.trim(),
+            None => return None,
+        };
+        return if line.is_empty() {
+            None
+        } else {
+            Some((n, line))
+        };
+    });
+}
+
+#[cfg(test)]
+mod test_text {
+    use crate::text::lines;
+    #[test]
+    fn test_lines() -> () {
+        let items: Vec<(usize, &str)> =
+            lines("# comment\n a b \n\n  # indented\nc # d\n").collect();
+        assert_eq!(vec![(1, "a b"), (4, "c")], items);
+    }
+}